@@ -0,0 +1,29 @@
+use std::env;
+
+use my_little_universe::read_main_config_file;
+use my_little_universe::save_load::load_or_create_universe;
+use my_little_universe::simulation::fast_forward;
+
+// Usage: simulate <universe_name> <config_name> <turns> [save_as_universe_name]
+fn main() {
+    let main_config = read_main_config_file();
+    let args: Vec<String> = env::args().collect();
+
+    let turns = match args.get(3).map(|turns| turns.parse::<u64>()) {
+        Some(Ok(turns)) => turns,
+        _ => panic!("Third argument of simulate is the amount of turns to run as an u64, got: {:?}", args)
+    };
+
+    let mut universe = load_or_create_universe(&main_config);
+    println!("Loaded universe with name {}", universe.universe_name());
+
+    let summary = fast_forward(&mut universe, turns);
+
+    match args.get(4) {
+        Some(save_as_universe_name) => universe.save_as(save_as_universe_name),
+        None => universe.save()
+    };
+
+    println!("{}", summary);
+    println!("{}", universe.economy_balance(None));
+}
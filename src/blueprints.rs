@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::amount::Amount;
+use crate::construct::construct::{Construct, speed_by_default};
+use crate::construct::construct_position::ConstructPositionSector;
+use crate::construct::mining_module::MiningModule;
+use crate::construct::production_module::ProductionModule;
+use crate::construct::storage_module::StorageModule;
+use crate::construct_module::ConstructModuleType;
+use crate::recipes::RecipeCatalog;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum BlueprintModule {
+    Production { name: String, recipe_id: String },
+    Storage(StorageModule),
+    Mining(MiningModule),
+}
+
+impl BlueprintModule {
+    fn name(&self) -> &str {
+        match self {
+            BlueprintModule::Production { name, .. } => name,
+            BlueprintModule::Storage(storage_module) => storage_module.name(),
+            BlueprintModule::Mining(mining_module) => mining_module.name(),
+        }
+    }
+}
+
+// Everything a shipyard needs to build a construct.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct Blueprint {
+    id: String,
+    cost: Vec<Amount>,
+    build_time: u32,
+    capacity: u32,
+    docker_modules: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    starting_modules: Vec<BlueprintModule>,
+    #[serde(default)]
+    modules: Vec<ConstructModuleType>,
+    #[serde(default = "speed_by_default")]
+    speed: u32,
+}
+
+impl Blueprint {
+    pub fn new(id: String, cost: Vec<Amount>, build_time: u32, capacity: u32, docker_modules: u32, starting_modules: Vec<BlueprintModule>) -> Self {
+        Blueprint { id, cost, build_time, capacity, docker_modules, starting_modules, modules: Vec::new(), speed: speed_by_default() }
+    }
+
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    pub fn cost(&self) -> &Vec<Amount> {
+        &self.cost
+    }
+    pub fn build_time(&self) -> u32 {
+        self.build_time
+    }
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+    pub fn docker_modules(&self) -> u32 {
+        self.docker_modules
+    }
+    pub fn modules(&self) -> &Vec<ConstructModuleType> {
+        &self.modules
+    }
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    pub fn build(&self, construct_name: String, sector_position: ConstructPositionSector) -> Construct {
+        let mut construct = Construct::new(construct_name, self.capacity, sector_position).with_speed(self.speed);
+        for _ in 0..self.docker_modules {
+            construct.position.install();
+        }
+        for module in &self.modules {
+            construct.install(module.clone()).expect("Blueprint module names are validated to be unique");
+        }
+        construct
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub struct BlueprintCatalog {
+    blueprints: Vec<Blueprint>,
+}
+
+impl BlueprintCatalog {
+    pub fn new(blueprints: Vec<Blueprint>) -> Self {
+        BlueprintCatalog { blueprints }
+    }
+
+    pub fn blueprints(&self) -> &Vec<Blueprint> {
+        &self.blueprints
+    }
+
+    pub fn get(&self, blueprint_id: &str) -> Option<&Blueprint> {
+        self.blueprints.iter().find(|blueprint| blueprint.id.eq(blueprint_id))
+    }
+
+    // Turns the starting modules into the modules that are installed, the production modules run the referenced recipe.
+    pub fn resolve_starting_modules(mut self, recipe_catalog: &RecipeCatalog) -> Self {
+        for blueprint in &mut self.blueprints {
+            for starting_module in blueprint.starting_modules.drain(..) {
+                let module = match starting_module {
+                    BlueprintModule::Production { name, recipe_id } => {
+                        let recipe = recipe_catalog.get(&recipe_id)
+                            .expect("Starting modules should only reference known recipes, validate the catalog first");
+                        ConstructModuleType::Production(ProductionModule::from_recipe(name, recipe, 0))
+                    }
+                    BlueprintModule::Storage(storage_module) => ConstructModuleType::Storage(storage_module),
+                    BlueprintModule::Mining(mining_module) => ConstructModuleType::Mining(mining_module),
+                };
+                blueprint.modules.push(module);
+            }
+        }
+        self
+    }
+
+    pub fn validate(&self, recipe_catalog: &RecipeCatalog) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let mut ids = HashSet::new();
+
+        for blueprint in &self.blueprints {
+            if blueprint.id.is_empty() {
+                errors.push("Blueprint id cannot be empty.".to_string());
+            }
+            if !ids.insert(blueprint.id.as_str()) {
+                errors.push(format!("Blueprint id {} is used more than once.", blueprint.id));
+            }
+            if blueprint.build_time == 0 {
+                errors.push(format!("Blueprint {} needs a build_time of at least 1.", blueprint.id));
+            }
+            if blueprint.speed == 0 {
+                errors.push(format!("Blueprint {} needs a speed of at least 1.", blueprint.id));
+            }
+            for amount in &blueprint.cost {
+                if amount.amount() == 0 {
+                    errors.push(format!("Blueprint {} has a cost of zero {}.", blueprint.id, amount.product()));
+                }
+            }
+
+            let mut module_names = HashSet::new();
+            for starting_module in &blueprint.starting_modules {
+                if !module_names.insert(starting_module.name()) {
+                    errors.push(format!("Blueprint {} has more than one module named {}.", blueprint.id, starting_module.name()));
+                }
+                match starting_module {
+                    BlueprintModule::Production { name, recipe_id } if recipe_catalog.get(recipe_id).is_none() => {
+                        errors.push(format!("Blueprint {} has module {} with the unknown recipe {}.", blueprint.id, name, recipe_id));
+                    }
+                    BlueprintModule::Mining(mining_module) if mining_module.extraction_per_turn() == 0 => {
+                        errors.push(format!("Blueprint {} has mining module {} with an extraction_per_turn of zero.", blueprint.id, mining_module.name()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub fn read_blueprint_catalog(config_name: &str, recipe_catalog: &RecipeCatalog) -> BlueprintCatalog {
+    let config_folder = "./config/".to_string().add(config_name);
+
+    let blueprints_config_path = config_folder.to_string().add("/blueprints.json");
+    println!("Using blueprints config blueprints_config_path: {}", blueprints_config_path);
+
+    let blueprints_config = fs::read_to_string(&blueprints_config_path)
+        .unwrap_or_else(|_| panic!("Something went wrong reading the file {}", blueprints_config_path));
+
+    let blueprint_catalog: BlueprintCatalog = serde_json::from_str(blueprints_config.as_str())
+        .unwrap_or_else(|_| panic!("Something went wrong parsing the file {}", blueprints_config_path));
+
+    if let Err(errors) = blueprint_catalog.validate(recipe_catalog) {
+        panic!("The blueprints in {} are not valid: {:?}", blueprints_config_path, errors);
+    }
+
+    blueprint_catalog.resolve_starting_modules(recipe_catalog)
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::blueprints::{Blueprint, BlueprintCatalog, BlueprintModule, read_blueprint_catalog};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct_position::ConstructPositionSector;
+    use crate::construct::storage_module::StorageModule;
+    use crate::products::{Product, ProductCategory};
+    use crate::recipes::read_recipe_catalog;
+    use crate::sector::SectorPosition;
+
+    #[test]
+    fn read_default_blueprints() {
+        let blueprint_catalog = read_blueprint_catalog("default", &read_recipe_catalog("default"));
+        let mine = blueprint_catalog.get("OreMine").unwrap();
+        assert_eq!(2, mine.modules().len());
+
+        let construct = mine.build("Mine".to_string(), ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0));
+        assert_eq!(500, construct.capacity());
+        assert_eq!(1, construct.position().docker_modules().len());
+        assert_eq!(1, construct.speed());
+        let transport = blueprint_catalog.get("Transport").unwrap().build("Hauler".to_string(), ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0));
+        assert_eq!(2, transport.speed());
+        assert!(construct.production_module("PowerToOre").is_some());
+        assert_eq!(1, blueprint_catalog.get("OreMiner").unwrap().modules().len());
+
+        assert!(read_blueprint_catalog("performance", &read_recipe_catalog("performance")).get("Transport").is_some());
+    }
+
+    #[test]
+    fn validation_finds_all_errors() {
+        let blueprint_catalog = BlueprintCatalog::new(vec![
+            Blueprint::new("Twice".to_string(), vec![Amount::new(Product::ware("Metals"), 0)], 0, 10, 0, vec![]).with_speed(0),
+            Blueprint::new("Twice".to_string(), vec![], 1, 10, 0, vec![
+                BlueprintModule::Production { name: "Mine".to_string(), recipe_id: "Unknown".to_string() },
+                BlueprintModule::Storage(StorageModule::new("Mine".to_string(), ProductCategory::Bulk, 10)),
+                BlueprintModule::Mining(serde_json::from_str(r#"{ "name": "Drill", "product": "Ores", "extraction_per_turn": 0 }"#).unwrap()),
+            ]),
+        ]);
+
+        assert_eq!(
+            Err(vec![
+                "Blueprint Twice needs a build_time of at least 1.".to_string(),
+                "Blueprint Twice needs a speed of at least 1.".to_string(),
+                "Blueprint Twice has a cost of zero Metals.".to_string(),
+                "Blueprint id Twice is used more than once.".to_string(),
+                "Blueprint Twice has module Mine with the unknown recipe Unknown.".to_string(),
+                "Blueprint Twice has more than one module named Mine.".to_string(),
+                "Blueprint Twice has mining module Drill with an extraction_per_turn of zero.".to_string(),
+            ]),
+            blueprint_catalog.validate(&read_recipe_catalog("default"))
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::construct::construct_position::ConstructPositionStatus;
+use crate::construct_module::ConstructModuleType;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::Product;
+use crate::sector::SectorPosition;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum BreakpointCondition {
+    StorageAtLeast { construct_name: String, product: Product, amount: u32 },
+    StorageAtMost { construct_name: String, product: Product, amount: u32 },
+    OutputStored { construct_name: String, module_name: String },
+    InSector { construct_name: String, sector_position: SectorPosition },
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Breakpoint {
+    name: String,
+    condition: BreakpointCondition,
+    condition_met: bool,
+}
+
+impl Breakpoint {
+    pub fn new(name: String, condition: BreakpointCondition) -> Self {
+        Breakpoint { name, condition, condition_met: false }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn condition(&self) -> &BreakpointCondition {
+        &self.condition
+    }
+    pub fn condition_met(&self) -> bool {
+        self.condition_met
+    }
+    pub(crate) fn product_mut(&mut self) -> Option<&mut Product> {
+        match &mut self.condition {
+            BreakpointCondition::StorageAtLeast { product, .. } | BreakpointCondition::StorageAtMost { product, .. } => Some(product),
+            _ => None
+        }
+    }
+
+    // Returns true if the condition just became true, that is when the breakpoint should fire.
+    pub fn update_condition_met(&mut self, condition_met: bool) -> bool {
+        let fired = condition_met && !self.condition_met;
+        self.condition_met = condition_met;
+        fired
+    }
+}
+
+impl MyLittleUniverse {
+    pub fn is_condition_met(&self, condition: &BreakpointCondition) -> bool {
+        match condition {
+            BreakpointCondition::StorageAtLeast { construct_name, product, amount } => {
+                match self.constructs.get(construct_name) {
+                    Some(construct) => construct.current_storage().get(product).unwrap_or(&0) >= amount,
+                    None => false
+                }
+            }
+            BreakpointCondition::StorageAtMost { construct_name, product, amount } => {
+                match self.constructs.get(construct_name) {
+                    Some(construct) => construct.current_storage().get(product).unwrap_or(&0) <= amount,
+                    None => false
+                }
+            }
+            BreakpointCondition::OutputStored { construct_name, module_name } => {
+                match self.constructs.get(construct_name) {
+                    Some(construct) => construct.modules().iter()
+                        .any(|module| match module {
+                            ConstructModuleType::Production(production_module) => {
+                                production_module.name().eq(module_name) && production_module.stored_output()
+                            }
+                            ConstructModuleType::Storage(_) | ConstructModuleType::Shipyard(_) | ConstructModuleType::Generator(_) | ConstructModuleType::Mining(_) => false
+                        }),
+                    None => false
+                }
+            }
+            BreakpointCondition::InSector { construct_name, sector_position } => {
+                self.current_sector_position(construct_name)
+                    .is_some_and(|current_sector_position| current_sector_position.eq(sector_position))
+            }
+        }
+    }
+
+    fn current_sector_position(&self, construct_name: &String) -> Option<&SectorPosition> {
+        match self.constructs.get(construct_name)?.position().position() {
+            ConstructPositionStatus::IsDocked(docked_at_name) => self.current_sector_position(docked_at_name),
+            ConstructPositionStatus::InSector(sector_position) => Some(sector_position.sector_position()),
+            ConstructPositionStatus::InTransit { .. } => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::breakpoint::{Breakpoint, BreakpointCondition};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEventType, InternalConstructEventType};
+    use crate::my_little_universe::{ExternalUniverseEventType, OfMove};
+    use crate::products::Product;
+    use crate::sector::SectorPosition;
+    use crate::time::{ExternalTimeEventType, TimeEventReturnType};
+    use crate::recipes::read_recipe_catalog;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn storage_threshold_pauses_time() {
+        let mut universe = generate_simple_universe("storage_threshold_pauses_time".to_string(), &read_recipe_catalog("default"));
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 10))));
+
+        assert_eq!(
+            ExternalCommandReturnValues::Time(TimeEventReturnType::Received),
+            universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(
+                "enough_ores".to_string(),
+                BreakpointCondition::StorageAtLeast { construct_name: "The_base_1".to_string(), product: Product::ware("Ores"), amount: 4 },
+            ))))
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Time(TimeEventReturnType::Denied("Breakpoint with name enough_ores already exists.".to_string())),
+            universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(
+                "enough_ores".to_string(),
+                BreakpointCondition::StorageAtLeast { construct_name: "The_base_1".to_string(), product: Product::ware("Ores"), amount: 4 },
+            ))))
+        );
+
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::StartUntilTurn(100)));
+        while universe.request_execute_turn() {}
+
+        assert_eq!(3, universe.time().turn());
+        assert!(universe.time().paused());
+        assert_eq!(&vec!["enough_ores".to_string()], universe.time().fired_breakpoints());
+
+        // Already met, so it does not fire again until it has been false in between.
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        assert!(universe.time().fired_breakpoints().is_empty());
+        for _ in 0..5 {
+            assert!(universe.request_execute_turn());
+        }
+        assert!(universe.time().fired_breakpoints().is_empty());
+    }
+
+    #[test]
+    fn entering_sector_pauses_time() {
+        let mut universe = generate_simple_universe("entering_sector_pauses_time".to_string(), &read_recipe_catalog("default"));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(
+            "arrived".to_string(),
+            BreakpointCondition::InSector { construct_name: "transport".to_string(), sector_position: SectorPosition::new(2, 2, 2) },
+        ))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        assert!(universe.request_execute_turn());
+        assert!(universe.time().fired_breakpoints().is_empty());
+
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), SectorPosition::new(2, 2, 2), None))));
+        assert!(universe.request_execute_turn());
+        assert_eq!(&vec!["arrived".to_string()], universe.time().fired_breakpoints());
+        assert!(!universe.request_execute_turn());
+
+        assert_eq!(
+            ExternalCommandReturnValues::Time(TimeEventReturnType::Received),
+            universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::RemoveBreakpoint("arrived".to_string())))
+        );
+        assert!(universe.time().breakpoints().is_empty());
+    }
+}
@@ -19,4 +19,7 @@ impl Amount {
     pub fn amount(&self) -> u32 {
         self.amount
     }
+    pub(crate) fn product_mut(&mut self) -> &mut Product {
+        &mut self.product
+    }
 }
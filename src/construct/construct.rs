@@ -1,14 +1,24 @@
 use std::cmp::min;
 use std::collections::HashMap;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::blueprints::Blueprint;
 use crate::construct::amount::Amount;
 use crate::construct::construct::ConstructEvenReturnType::{RequestLoadProcessed, RequestUnloadProcessed};
 use crate::construct::construct_position::{ConstructPositionEventReturnType, ConstructPositionEventType, ConstructPositionSector, ConstructPositionState, ExternalConstructPositionEventType, InternalConstructPositionEventType};
-use crate::construct::production_module::ProductionModule;
+use crate::construct::production_module::{IdleReason, OrderTarget, ProductionModule, ProductionOrder};
+use crate::construct::shipyard_module::ShipyardOrder;
+use crate::construct::storage_module::{HoldFillLevel, StorageCapacity};
 use crate::construct_module::{CanHandleNextTurn, ConstructModuleType};
+use crate::credits::Payment;
+use crate::market::{MarketOrder, OrderSide};
+use crate::module_levels::ModuleLevel;
+use crate::power::PowerState;
 use crate::products::Product;
+use crate::recipes::RecipeCatalog;
+use crate::sector::Sector;
+use crate::wear::WearCatalog;
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum ConstructEventType {
@@ -22,12 +32,41 @@ pub enum InternalConstructEventType {
     ConstructPosition(InternalConstructPositionEventType),
     RequestLoad(Amount),
     RequestUnload(Amount),
+    UpgradeModule { module_name: String, module_level: ModuleLevel },
+    BuildConstruct { module_name: String, blueprint: Blueprint, construct_name: String },
+    AddProductionOrder { module_name: String, order: ProductionOrder },
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum ExternalConstructEventType {
     GetConstructState { include_stack: bool },
     ConstructPosition(ExternalConstructPositionEventType),
+    SetModulePriority { module_name: String, priority: u32 },
+    SetModuleEnabled { module_name: String, enabled: bool },
+    SetAllocationMode(AllocationMode),
+    UpgradeModule { module_name: String },
+    // With a price the universe settles the payment in the same operation as the install.
+    // The installed module gets installed_name, so more modules built with the same name can be installed.
+    InstallModule { module_name: String, installed_name: Option<String>, price: Option<Payment> },
+    UninstallModule { module_name: String },
+    BuildConstruct { module_name: String, blueprint_id: String, construct_name: String },
+    AddProductionOrder { module_name: String, recipe_id: String, target: OrderTarget },
+    RemoveProductionOrder { module_name: String, index: usize },
+    SetPowerSharing(bool),
+    SetMaintenance(bool),
+    PostMarketOrder { side: OrderSide, product: Product, quantity: u32, limit_price: u32 },
+    CancelMarketOrder { order_id: u64 },
+    SetOwner(Option<String>),
+}
+
+// Decides which production module gets to take its inputs first when they compete for the same products.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub enum AllocationMode {
+    // Highest priority first, install order breaks ties.
+    #[default]
+    Priority,
+    // Round robin, every turn another module goes first.
+    FairShare,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -37,21 +76,73 @@ pub enum ConstructEvenReturnType {
     ConstructState(Construct),
     TurnExecuted,
     ConstructPosition(ConstructPositionEventReturnType),
+    ModuleUpdated,
+    AllocationModeSet,
+    ModuleUpgraded(u32),
+    ModuleInstalled,
+    ModuleUninstalled,
+    BuildOrdered,
+    ProductionOrderAdded(usize),
+    ProductionOrderRemoved(ProductionOrder),
+    PowerSharingSet,
+    MaintenanceSet,
+    MarketOrderPosted(u64),
+    MarketOrderCancelled(MarketOrder),
+    OwnerSet,
+    Denied(String),
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Construct {
     name: String,
     capacity: u32,
+    #[serde(with = "stored_amounts")]
     current_storage: HashMap<Product, u32>,
     modules: Vec<ConstructModuleType>,
     event_stack: Vec<ConstructEventType>,
     pub(crate) position: ConstructPositionState,
+    // Only filled in the construct state that is returned by GetConstructState.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hold_fill_levels: Vec<HoldFillLevel>,
+    #[serde(default)]
+    allocation_mode: AllocationMode,
+    // Set by the power phase at the start of the production phase.
+    #[serde(default)]
+    pub(crate) power: PowerState,
+    // A docked construct that shares power joins the power grid of its host.
+    #[serde(default)]
+    share_power: bool,
+    // Maintenance keeps the modules in condition, as long as the storage holds the maintenance cost.
+    #[serde(default = "maintenance_by_default")]
+    maintenance: bool,
+    #[serde(default)]
+    market_orders: Vec<MarketOrder>,
+    #[serde(default)]
+    next_market_order_id: u64,
+    // An owned construct pays from and is paid into the wallet of its owner.
+    #[serde(default)]
+    owner: Option<String>,
+    // Sectors per turn when moving between sectors.
+    #[serde(default = "speed_by_default", deserialize_with = "deserialize_speed")]
+    speed: u32,
+}
+
+pub(crate) fn speed_by_default() -> u32 {
+    1
+}
+
+// A construct with a speed of zero would never arrive, so a save is read with a speed of at least 1.
+fn deserialize_speed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    Ok(u32::deserialize(deserializer)?.max(1))
+}
+
+fn maintenance_by_default() -> bool {
+    true
 }
 
 impl Construct {
     pub fn new(name: String, capacity: u32, sector_position: ConstructPositionSector) -> Self {
-        Construct { name: name.clone(), capacity, current_storage: HashMap::new(), modules: Vec::new(), event_stack: Vec::new(), position: ConstructPositionState::new(sector_position) }
+        Construct { name: name.clone(), capacity, current_storage: HashMap::new(), modules: Vec::new(), event_stack: Vec::new(), position: ConstructPositionState::new(sector_position), hold_fill_levels: Vec::new(), allocation_mode: AllocationMode::default(), power: PowerState::default(), share_power: false, maintenance: maintenance_by_default(), market_orders: Vec::new(), next_market_order_id: 0, owner: None, speed: speed_by_default() }
     }
 
     pub fn name(&self) -> &str {
@@ -72,6 +163,35 @@ impl Construct {
     pub fn position(&self) -> &ConstructPositionState {
         &self.position
     }
+    pub fn hold_fill_levels(&self) -> &Vec<HoldFillLevel> {
+        &self.hold_fill_levels
+    }
+    pub fn allocation_mode(&self) -> AllocationMode {
+        self.allocation_mode
+    }
+    pub fn power(&self) -> &PowerState {
+        &self.power
+    }
+    pub fn shares_power(&self) -> bool {
+        self.share_power
+    }
+    pub fn maintenance(&self) -> bool {
+        self.maintenance
+    }
+    pub fn owner(&self) -> &Option<String> {
+        &self.owner
+    }
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed.max(1);
+        self
+    }
+    pub fn market_orders(&self) -> &Vec<MarketOrder> {
+        &self.market_orders
+    }
 
     pub fn push_event(&mut self, event: &ConstructEventType) -> ConstructEvenReturnType {
         // self.event_stack.push(event.clone());
@@ -81,13 +201,12 @@ impl Construct {
     fn handle_event(&mut self, event: &ConstructEventType) -> ConstructEvenReturnType {
         return match event {
             ConstructEventType::External(ExternalConstructEventType::GetConstructState { include_stack }) => {
-                if *include_stack {
-                    ConstructEvenReturnType::ConstructState(self.clone())
-                } else {
-                    let mut state = self.clone();
+                let mut state = self.clone();
+                state.hold_fill_levels = self.storage_capacity().fill_levels(&self.current_storage);
+                if !*include_stack {
                     state.event_stack = Vec::new();
-                    ConstructEvenReturnType::ConstructState(state)
                 }
+                ConstructEvenReturnType::ConstructState(state)
             }
             ConstructEventType::Internal(InternalConstructEventType::RequestLoad(request)) => {
                 RequestLoadProcessed(self.load_request(request))
@@ -106,6 +225,108 @@ impl Construct {
                 }
                 ConstructEvenReturnType::ConstructPosition(self.position.handle_event(&ConstructPositionEventType::External(construct_position_event.clone())))
             }
+            ConstructEventType::External(ExternalConstructEventType::SetModulePriority { module_name, priority }) => {
+                match self.production_module_mut(module_name) {
+                    Some(production_module) => {
+                        production_module.set_priority(*priority);
+                        ConstructEvenReturnType::ModuleUpdated
+                    }
+                    None => ConstructEvenReturnType::Denied(format!("No production module named {} installed.", module_name))
+                }
+            }
+            ConstructEventType::External(ExternalConstructEventType::SetModuleEnabled { module_name, enabled }) => {
+                match self.production_module_mut(module_name) {
+                    Some(production_module) => {
+                        production_module.set_enabled(*enabled);
+                        ConstructEvenReturnType::ModuleUpdated
+                    }
+                    None => ConstructEvenReturnType::Denied(format!("No production module named {} installed.", module_name))
+                }
+            }
+            ConstructEventType::External(ExternalConstructEventType::SetAllocationMode(allocation_mode)) => {
+                self.allocation_mode = *allocation_mode;
+                ConstructEvenReturnType::AllocationModeSet
+            }
+            ConstructEventType::External(ExternalConstructEventType::SetPowerSharing(share_power)) => {
+                self.share_power = *share_power;
+                ConstructEvenReturnType::PowerSharingSet
+            }
+            ConstructEventType::External(ExternalConstructEventType::SetMaintenance(maintenance)) => {
+                self.maintenance = *maintenance;
+                ConstructEvenReturnType::MaintenanceSet
+            }
+            ConstructEventType::External(ExternalConstructEventType::PostMarketOrder { side, product, quantity, limit_price }) => {
+                if *quantity == 0 {
+                    return ConstructEvenReturnType::Denied("A market order needs a quantity above zero.".to_string());
+                }
+                let order_id = self.next_market_order_id;
+                self.next_market_order_id += 1;
+                self.market_orders.push(MarketOrder::new(order_id, *side, product.clone(), *quantity, *limit_price));
+                ConstructEvenReturnType::MarketOrderPosted(order_id)
+            }
+            ConstructEventType::External(ExternalConstructEventType::CancelMarketOrder { order_id }) => {
+                match self.market_orders.iter().position(|order| order.id() == *order_id) {
+                    Some(index) => ConstructEvenReturnType::MarketOrderCancelled(self.market_orders.remove(index)),
+                    None => ConstructEvenReturnType::Denied(format!("No market order with id {}.", order_id))
+                }
+            }
+            ConstructEventType::External(ExternalConstructEventType::UpgradeModule { .. }) => {
+                ConstructEvenReturnType::Denied("UpgradeModule needs the module levels of the universe, use the internal event instead.".to_string())
+            }
+            ConstructEventType::External(ExternalConstructEventType::InstallModule { module_name, installed_name, price: None }) => {
+                self.install_from_storage(module_name, installed_name)
+            }
+            ConstructEventType::External(ExternalConstructEventType::InstallModule { .. }) => {
+                ConstructEvenReturnType::Denied("InstallModule with a price needs the wallets of the universe, send it to the universe instead.".to_string())
+            }
+            ConstructEventType::External(ExternalConstructEventType::SetOwner(owner)) => {
+                self.owner = owner.clone();
+                ConstructEvenReturnType::OwnerSet
+            }
+            ConstructEventType::External(ExternalConstructEventType::UninstallModule { module_name }) => {
+                self.uninstall_to_storage(module_name)
+            }
+            ConstructEventType::External(ExternalConstructEventType::AddProductionOrder { .. }) => {
+                ConstructEvenReturnType::Denied("AddProductionOrder needs the recipes of the universe, use the internal event instead.".to_string())
+            }
+            ConstructEventType::Internal(InternalConstructEventType::AddProductionOrder { module_name, order }) => {
+                match self.production_module_mut(module_name) {
+                    Some(production_module) => {
+                        production_module.add_order(order.clone());
+                        ConstructEvenReturnType::ProductionOrderAdded(production_module.orders().len() - 1)
+                    }
+                    None => ConstructEvenReturnType::Denied(format!("No production module named {} installed.", module_name))
+                }
+            }
+            ConstructEventType::External(ExternalConstructEventType::RemoveProductionOrder { module_name, index }) => {
+                match self.production_module_mut(module_name) {
+                    Some(production_module) => match production_module.remove_order(*index) {
+                        Ok(order) => ConstructEvenReturnType::ProductionOrderRemoved(order),
+                        Err(message) => ConstructEvenReturnType::Denied(message)
+                    },
+                    None => ConstructEvenReturnType::Denied(format!("No production module named {} installed.", module_name))
+                }
+            }
+            ConstructEventType::External(ExternalConstructEventType::BuildConstruct { .. }) => {
+                ConstructEvenReturnType::Denied("BuildConstruct needs the blueprints of the universe, use the internal event instead.".to_string())
+            }
+            ConstructEventType::Internal(InternalConstructEventType::BuildConstruct { module_name, blueprint, construct_name }) => {
+                let shipyard_module = self.modules.iter_mut()
+                    .find_map(|module| match module {
+                        ConstructModuleType::Shipyard(shipyard_module) if shipyard_module.name().eq(module_name) => Some(shipyard_module),
+                        _ => None
+                    });
+                match shipyard_module {
+                    Some(shipyard_module) => match shipyard_module.start_order(construct_name.clone(), blueprint.clone()) {
+                        Ok(()) => ConstructEvenReturnType::BuildOrdered,
+                        Err(message) => ConstructEvenReturnType::Denied(message)
+                    },
+                    None => ConstructEvenReturnType::Denied(format!("No shipyard module named {} installed.", module_name))
+                }
+            }
+            ConstructEventType::Internal(InternalConstructEventType::UpgradeModule { module_name, module_level }) => {
+                self.upgrade_module(module_name, module_level)
+            }
             ConstructEventType::Internal(InternalConstructEventType::ExecuteTurn(current_turn)) => {
                 self.next_turn(&current_turn);
                 ConstructEvenReturnType::TurnExecuted
@@ -120,12 +341,24 @@ impl Construct {
         unload(&mut self.current_storage, amount)
     }
 
+    pub fn used_volume(&self) -> u32 {
+        used_volume(&self.current_storage)
+    }
+
+    pub fn storage_capacity(&self) -> StorageCapacity {
+        StorageCapacity::new(self.capacity, &self.modules)
+    }
+
+    pub fn has_room_for(&self, amounts: &Vec<Amount>) -> bool {
+        self.storage_capacity().fits(&self.current_storage, amounts)
+    }
+
     pub(crate) fn load_request(&mut self, amount: &Amount) -> u32 {
-        let leftover_capacity = self.capacity - self.current_storage.values().sum::<u32>();
-        let amount_to_be_stored = min(leftover_capacity, amount.amount());
+        let leftover_capacity = self.storage_capacity().free_volume_for(&self.current_storage, amount.product().category());
+        let amount_to_be_stored = min(leftover_capacity / amount.product().volume(), amount.amount());
 
         if amount_to_be_stored == 0 {
-            return amount.amount();
+            return 0;
         }
 
         load(&mut self.current_storage, &Amount::new(amount.product().clone(), amount_to_be_stored));
@@ -133,6 +366,239 @@ impl Construct {
         amount_to_be_stored
     }
 
+    // Stored modules with the same name can differ, the one in the best condition is installed first.
+    // Their json breaks ties, so the choice does not depend on the order of the storage.
+    fn install_from_storage(&mut self, module_name: &str, installed_name: &Option<String>) -> ConstructEvenReturnType {
+        let module = match self.current_storage.keys()
+            .filter_map(|product| match product {
+                Product::Module(module) if module.name().eq(module_name) => Some(module.as_ref()),
+                _ => None
+            })
+            .min_by_key(|module| (std::cmp::Reverse(module.wear().map_or(0, |wear| wear.condition())), serde_json::to_string(module).unwrap_or_default())) {
+            Some(module) => module.clone(),
+            None => return ConstructEvenReturnType::Denied(format!("No module named {} in storage.", module_name))
+        };
+
+        let mut installed_module = module.clone();
+        if let Some(installed_name) = installed_name {
+            installed_module.set_name(installed_name.clone());
+        }
+        if let Err(message) = self.install(installed_module) {
+            return ConstructEvenReturnType::Denied(message);
+        }
+        unload(&mut self.current_storage, &Amount::new(Product::module(module), 1));
+        ConstructEvenReturnType::ModuleInstalled
+    }
+
+    fn uninstall_to_storage(&mut self, module_name: &str) -> ConstructEvenReturnType {
+        let module = match self.modules.iter().find(|module| module.name().eq(module_name)) {
+            Some(module) => module.clone(),
+            None => return ConstructEvenReturnType::Denied(format!("No module named {} installed.", module_name))
+        };
+
+        match &module {
+            ConstructModuleType::Production(production_module) if production_module.stored_input() || production_module.stored_output() => {
+                return ConstructEvenReturnType::Denied(format!("Module {} is still producing.", module_name));
+            }
+            ConstructModuleType::Shipyard(shipyard_module) if shipyard_module.order().is_some() => {
+                return ConstructEvenReturnType::Denied(format!("Shipyard {} is still building.", module_name));
+            }
+            _ => {}
+        }
+
+        // Removing a storage module shrinks the holds, so the room is checked without the module.
+        let remaining_modules: Vec<ConstructModuleType> = self.modules.iter()
+            .filter(|installed| installed.name().ne(module_name))
+            .cloned()
+            .collect();
+        let module_cargo = Amount::new(Product::module(module), 1);
+        if !StorageCapacity::new(self.capacity, &remaining_modules).fits(&self.current_storage, &vec![module_cargo.clone()]) {
+            return ConstructEvenReturnType::Denied(format!("No room to store module {}.", module_name));
+        }
+
+        if let Err(message) = self.uninstall(&module_name.to_string()) {
+            return ConstructEvenReturnType::Denied(message);
+        }
+        load(&mut self.current_storage, &module_cargo);
+        ConstructEvenReturnType::ModuleUninstalled
+    }
+
+    fn upgrade_module(&mut self, module_name: &str, module_level: &ModuleLevel) -> ConstructEvenReturnType {
+        match self.production_module(module_name) {
+            Some(production_module) => {
+                if production_module.level() + 1 != module_level.level() {
+                    return ConstructEvenReturnType::Denied(format!("Module {} is level {} and cannot be upgraded to level {}.", module_name, production_module.level(), module_level.level()));
+                }
+            }
+            None => return ConstructEvenReturnType::Denied(format!("No production module named {} installed.", module_name))
+        }
+
+        let missing_cost = module_level.cost().iter()
+            .find(|amount| self.current_storage.get(amount.product()).unwrap_or(&0) < &amount.amount());
+        if let Some(amount) = missing_cost {
+            return ConstructEvenReturnType::Denied(format!("Upgrading {} to level {} needs {} {}.", module_name, module_level.level(), amount.amount(), amount.product()));
+        }
+
+        for amount in module_level.cost() {
+            unload(&mut self.current_storage, amount);
+        }
+        self.production_module_mut(module_name).unwrap().upgrade(module_level);
+        ConstructEvenReturnType::ModuleUpgraded(module_level.level())
+    }
+
+    pub fn shipyard_orders(&self) -> Vec<&ShipyardOrder> {
+        self.modules.iter()
+            .filter_map(|module| match module {
+                ConstructModuleType::Shipyard(shipyard_module) => shipyard_module.order().as_ref(),
+                _ => None
+            })
+            .collect()
+    }
+
+    pub(crate) fn take_finished_orders(&mut self, current_turn: &u64) -> Vec<ShipyardOrder> {
+        self.modules.iter_mut()
+            .filter_map(|module| match module {
+                ConstructModuleType::Shipyard(shipyard_module) => shipyard_module.take_finished_order(current_turn),
+                _ => None
+            })
+            .collect()
+    }
+
+    pub fn production_module(&self, module_name: &str) -> Option<&ProductionModule> {
+        self.modules.iter()
+            .find_map(|module| match module {
+                ConstructModuleType::Production(production_module) if production_module.name().eq(module_name) => Some(production_module),
+                _ => None
+            })
+    }
+
+    fn production_module_mut(&mut self, module_name: &str) -> Option<&mut ProductionModule> {
+        self.modules.iter_mut()
+            .find_map(|module| match module {
+                ConstructModuleType::Production(production_module) if production_module.name().eq(module_name) => Some(production_module),
+                _ => None
+            })
+    }
+
+    // Installed modules and module cargo both pick up the recipes as they are in the catalog now.
+    pub(crate) fn refresh_recipes(&mut self, recipe_catalog: &RecipeCatalog) {
+        for module in &mut self.modules {
+            if let ConstructModuleType::Production(production_module) = module {
+                production_module.refresh_recipes(recipe_catalog);
+            }
+        }
+        // The module cargo is a storage key, so it is taken out and stored again.
+        let module_cargo: Vec<Product> = self.current_storage.keys()
+            .filter(|product| matches!(product, Product::Module(_)))
+            .cloned()
+            .collect();
+        for product in module_cargo {
+            let amount = self.current_storage.remove(&product).unwrap_or(0);
+            let product = match product {
+                Product::Module(mut module) => {
+                    if let ConstructModuleType::Production(production_module) = module.as_mut() {
+                        production_module.refresh_recipes(recipe_catalog);
+                    }
+                    Product::Module(module)
+                }
+                product => product
+            };
+            *self.current_storage.entry(product).or_insert(0) += amount;
+        }
+    }
+
+    // Generators that have their fuel produce power, it is gone when it is not used this turn.
+    pub(crate) fn generate_power(&mut self) -> u32 {
+        let current_storage = &mut self.current_storage;
+        self.modules.iter_mut()
+            .filter_map(|module| match module {
+                ConstructModuleType::Generator(generator_module) => Some(generator_module.generate(current_storage)),
+                _ => None
+            })
+            .sum()
+    }
+
+    pub(crate) fn wear_and_maintain(&mut self, wear_catalog: &WearCatalog) {
+        for module in &mut self.modules {
+            if let Some((module_type, wear)) = module.wear_mut() {
+                wear.apply(wear_catalog.wear_per_cycle(&module_type));
+
+                let can_pay = wear_catalog.maintenance_cost().iter()
+                    .all(|amount| self.current_storage.get(amount.product()).unwrap_or(&0) >= &amount.amount());
+                if self.maintenance && wear.needs_maintenance() && wear_catalog.condition_per_maintenance() > 0 && can_pay {
+                    for amount in wear_catalog.maintenance_cost() {
+                        unload(&mut self.current_storage, amount);
+                    }
+                    wear.repair(wear_catalog.condition_per_maintenance());
+                }
+            }
+        }
+    }
+
+    // Filled orders leave the book.
+    pub(crate) fn update_market_order(&mut self, updated_order: &MarketOrder) {
+        if updated_order.quantity() == 0 {
+            self.market_orders.retain(|order| order.id() != updated_order.id());
+        } else if let Some(order) = self.market_orders.iter_mut().find(|order| order.id() == updated_order.id()) {
+            *order = updated_order.clone();
+        }
+    }
+
+    // Mining modules take their turn in the order they were installed, as far as there is room.
+    pub(crate) fn mine(&mut self, sector: &mut Sector, group_address: usize) {
+        let storage_capacity = self.storage_capacity();
+        for module in &mut self.modules {
+            if let ConstructModuleType::Mining(mining_module) = module {
+                let product = mining_module.product();
+                let room = storage_capacity.free_volume_for(&self.current_storage, product.category()) / product.volume();
+                let mined = sector.extract(group_address, &product, mining_module.extraction(room));
+                if mined > 0 {
+                    load(&mut self.current_storage, &Amount::new(product, mined));
+                }
+                mining_module.record_turn(mined);
+            }
+        }
+    }
+
+    // Name, priority and power draw of the modules that are in the middle of a batch.
+    pub(crate) fn power_demands(&self) -> Vec<(String, u32, u32)> {
+        self.modules.iter()
+            .filter_map(|module| match module {
+                ConstructModuleType::Production(production_module) if production_module.needs_power() => {
+                    Some((production_module.name().to_string(), production_module.priority(), production_module.power_draw()))
+                }
+                _ => None
+            })
+            .collect()
+    }
+
+    // Indexes of the enabled production modules in the order they may take their inputs this turn.
+    fn production_order(&self, current_turn: &u64) -> Vec<usize> {
+        let mut order: Vec<usize> = self.modules.iter()
+            .enumerate()
+            .filter_map(|(index, module)| match module {
+                ConstructModuleType::Production(production_module) if production_module.enabled() => Some(index),
+                _ => None
+            })
+            .collect();
+
+        match self.allocation_mode {
+            AllocationMode::Priority => {
+                order.sort_by_key(|index| match &self.modules[*index] {
+                    ConstructModuleType::Production(production_module) => std::cmp::Reverse(production_module.priority()),
+                    _ => std::cmp::Reverse(0),
+                });
+            }
+            AllocationMode::FairShare => {
+                if !order.is_empty() {
+                    let first = (*current_turn % order.len() as u64) as usize;
+                    order.rotate_left(first);
+                }
+            }
+        }
+        order
+    }
+
     pub fn install(&mut self, new_module: ConstructModuleType) -> Result<(), String> {
         if self.modules.iter()
             .find(|m| m.name().eq(new_module.name()))
@@ -158,18 +624,92 @@ impl Construct {
 
 impl CanHandleNextTurn for Construct {
     fn next_turn(&mut self, current_turn: &u64) {
-        for module in &mut self.modules {
-            match module {
-                ConstructModuleType::Production(production_module) => {
-                    handle_production_output(&mut self.current_storage, self.capacity, current_turn, production_module);
-                    handle_production_input(&mut self.current_storage, current_turn, production_module);
-                    production_module.handle_turn(current_turn);
+        let storage_capacity = self.storage_capacity();
+        let production_order = self.production_order(current_turn);
+        let mut active_modules = Vec::new();
+        let mut stalled_modules = Vec::new();
+        for index in &production_order {
+            if let ConstructModuleType::Production(production_module) = &mut self.modules[*index] {
+                // Without power or broken down the batch makes no progress this turn.
+                let stall_reason = if production_module.wear().is_broken_down() {
+                    Some(IdleReason::BrokenDown)
+                } else if self.power.is_unpowered(production_module.name()) {
+                    Some(IdleReason::NoPower)
+                } else {
+                    None
+                };
+                if let Some(reason) = stall_reason {
+                    production_module.stall(current_turn);
+                    stalled_modules.push((*index, reason));
+                    continue;
+                }
+                let stored_output = handle_production_output(&mut self.current_storage, &storage_capacity, current_turn, production_module);
+                production_module.advance_orders(&self.current_storage);
+                // Finishing the last order stops the module before it takes new input.
+                let took_input = production_module.enabled() && handle_production_input(&mut self.current_storage, current_turn, production_module);
+                production_module.handle_turn(current_turn);
+                if stored_output || took_input {
+                    active_modules.push(*index);
+                }
+            }
+        }
+        for (index, module) in self.modules.iter_mut().enumerate() {
+            if let ConstructModuleType::Production(production_module) = module {
+                if let Some((_, reason)) = stalled_modules.iter().find(|(stalled_index, _)| *stalled_index == index) {
+                    production_module.record_stalled_turn(reason);
+                    continue;
                 }
+                production_module.record_turn(&self.current_storage, current_turn, active_modules.contains(&index));
+            }
+        }
+        for module in &mut self.modules {
+            if let ConstructModuleType::Shipyard(shipyard_module) = module {
+                shipyard_module.handle_turn(&mut self.current_storage, current_turn);
             }
         }
     }
 }
 
+// Modules cannot be json map keys, so the storage is saved as a list of amounts.
+mod stored_amounts {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::construct::amount::Amount;
+    use crate::products::Product;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredAmounts {
+        List(Vec<Amount>),
+        // Saves from before modules could be stored.
+        Map(HashMap<Product, u32>),
+    }
+
+    pub fn serialize<S: Serializer>(current_storage: &HashMap<Product, u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        current_storage.iter()
+            .map(|(product, amount)| Amount::new(product.clone(), *amount))
+            .collect::<Vec<Amount>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<Product, u32>, D::Error> {
+        Ok(match StoredAmounts::deserialize(deserializer)? {
+            StoredAmounts::List(amounts) => amounts.into_iter()
+                .map(|amount| (amount.product().clone(), amount.amount()))
+                .collect(),
+            StoredAmounts::Map(current_storage) => current_storage,
+        })
+    }
+}
+
+fn used_volume(current_storage: &HashMap<Product, u32>) -> u32 {
+    current_storage.iter()
+        .map(|(product, amount)| product.volume() * amount)
+        .sum::<u32>()
+}
+
 fn load(current_storage: &mut HashMap<Product, u32>, amount: &Amount) {
     match current_storage.get_mut(amount.product()) {
         Some(amount_stored) => {
@@ -181,7 +721,7 @@ fn load(current_storage: &mut HashMap<Product, u32>, amount: &Amount) {
     }
 }
 
-fn unload(current_storage: &mut HashMap<Product, u32>, amount: &Amount) -> u32 {
+pub(crate) fn unload(current_storage: &mut HashMap<Product, u32>, amount: &Amount) -> u32 {
     match current_storage.get_mut(amount.product()) {
         Some(amount_stored) => {
             if *amount_stored > amount.amount() {
@@ -200,74 +740,91 @@ fn unload(current_storage: &mut HashMap<Product, u32>, amount: &Amount) -> u32 {
     }
 }
 
-fn handle_production_output(current_storage: &mut HashMap<Product, u32>, capacity: u32, current_turn: &u64, production_module: &mut ProductionModule) {
+fn handle_production_output(current_storage: &mut HashMap<Product, u32>, storage_capacity: &StorageCapacity, current_turn: &u64, production_module: &mut ProductionModule) -> bool {
     if let Some(amounts) = production_module.will_output(current_turn) {
-        let total_output = amounts.iter()
-            .map(|amount| amount.amount())
-            .sum::<u32>();
-        let total_free_capacity = capacity - current_storage.values().sum::<u32>();
-
-        if total_output <= total_free_capacity {
-            for amount in amounts {
+        if storage_capacity.fits(current_storage, &amounts) {
+            for amount in &amounts {
                 load(current_storage, amount);
             }
             production_module.set_stored_output(false);
             production_module.set_stored_input(false);
+            production_module.record_output(&amounts);
+            return true;
         } else {
             production_module.set_stored_output(true);
         }
     }
+    false
 }
 
-fn handle_production_input(current_storage: &mut HashMap<Product, u32>, current_turn: &u64, production_module: &mut ProductionModule) {
-    if let Some(amounts) = production_module.require_input(current_turn) {
+fn handle_production_input(current_storage: &mut HashMap<Product, u32>, current_turn: &u64, production_module: &mut ProductionModule) -> bool {
+    if let Some(amounts) = production_module.require_input(current_turn).cloned() {
         let any_product_not_stored = amounts.iter()
             .find(|amount| current_storage.get(amount.product()) == None
                 || current_storage.get(amount.product()).unwrap() < &amount.amount());
 
         if any_product_not_stored == None {
-            for amount in amounts {
+            for amount in &amounts {
                 unload(current_storage, amount);
             }
             production_module.set_stored_input(true);
+            production_module.record_input(&amounts);
+            return true;
         }
     }
+    false
 }
 
 #[cfg(test)]
 mod tests_int {
     use crate::construct::amount::Amount;
-    use crate::construct::construct::{Construct, ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::construct::{AllocationMode, Construct, ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
     use crate::construct::construct_position::{ConstructPositionEventReturnType, ConstructPositionSector, ExternalConstructPositionEventType, InternalConstructPositionEventType};
     use crate::construct::construct_position::ConstructPositionStatus::{InSector, IsDocked};
-    use crate::construct::production_module::ProductionModule;
-    use crate::construct_module::ConstructModuleType::Production;
-    use crate::products::Product;
+    use crate::construct::production_module::{IdleReason, ProductionModule};
+    use crate::construct::storage_module::StorageModule;
+    use crate::construct_module::ConstructModuleType::{Production, Storage};
+    use crate::products::{Product, ProductCategory};
     use crate::sector::SectorPosition;
 
     #[test]
     fn load_and_unload_tries_its_best() {
         let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
         let mut construct = Construct::new("The base".to_string(), 500, sector_position);
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
 
-        assert_eq!(500, request_load(&mut construct, Amount::new(Product::PowerCells, 700)));
-        assert_eq!(500, *construct.current_storage.get(&Product::PowerCells).unwrap());
+        assert_eq!(500, request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 700)));
+        assert_eq!(500, *construct.current_storage.get(&Product::ware("PowerCells")).unwrap());
 
-        assert_eq!(500, request_unload(&mut construct, Amount::new(Product::PowerCells, 700)));
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
+        assert_eq!(500, request_unload(&mut construct, Amount::new(Product::ware("PowerCells"), 700)));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
 
-        assert_eq!(500, request_load(&mut construct, Amount::new(Product::PowerCells, 700)));
-        assert_eq!(500, *construct.current_storage.get(&Product::PowerCells).unwrap());
+        assert_eq!(500, request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 700)));
+        assert_eq!(500, *construct.current_storage.get(&Product::ware("PowerCells")).unwrap());
 
-        assert_eq!(700, request_load(&mut construct, Amount::new(Product::PowerCells, 700)));
-        assert_eq!(500, *construct.current_storage.get(&Product::PowerCells).unwrap());
+        assert_eq!(0, request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 700)));
+        assert_eq!(500, *construct.current_storage.get(&Product::ware("PowerCells")).unwrap());
 
-        assert_eq!(500, request_unload(&mut construct, Amount::new(Product::PowerCells, 700)));
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
+        assert_eq!(500, request_unload(&mut construct, Amount::new(Product::ware("PowerCells"), 700)));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
 
-        assert_eq!(0, request_unload(&mut construct, Amount::new(Product::PowerCells, 700)));
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
+        assert_eq!(0, request_unload(&mut construct, Amount::new(Product::ware("PowerCells"), 700)));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
+    }
+
+    #[test]
+    fn load_uses_volume() {
+        let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
+        let mut construct = Construct::new("The base".to_string(), 500, sector_position);
+        let module = Product::module(Production(ProductionModule::new("Factory".to_string(), vec![], vec![], 1, 0)));
+
+        assert_eq!(50, request_load(&mut construct, Amount::new(module.clone(), 60)));
+        assert_eq!(500, construct.used_volume());
+
+        assert_eq!(1, request_unload(&mut construct, Amount::new(module.clone(), 1)));
+        assert_eq!(10, request_load(&mut construct, Amount::new(Product::ware("Ores"), 20)));
+        assert_eq!(500, construct.used_volume());
+        assert_eq!(Some(&49), construct.current_storage.get(&module));
     }
 
     #[test]
@@ -276,15 +833,15 @@ mod tests_int {
         let mut construct = Construct::new("The base".to_string(), 500, sector_position);
         let ore_production = ProductionModule::new(
             "PowerToOre".to_string(),
-            vec![Amount::new(Product::PowerCells, 1)],
-            vec![Amount::new(Product::Ores, 2)],
+            vec![Amount::new(Product::ware("PowerCells"), 1)],
+            vec![Amount::new(Product::ware("Ores"), 2)],
             1,
             0,
         );
         let metal_production = ProductionModule::new(
             "OreAndEnergyToMetal".to_string(),
-            vec![Amount::new(Product::PowerCells, 2), Amount::new(Product::Ores, 4)],
-            vec![Amount::new(Product::Metals, 1)],
+            vec![Amount::new(Product::ware("PowerCells"), 2), Amount::new(Product::ware("Ores"), 4)],
+            vec![Amount::new(Product::ware("Metals"), 1)],
             3,
             0,
         );
@@ -350,84 +907,84 @@ mod tests_int {
         let mut construct = Construct::new("The base".to_string(), 500, sector_position.clone());
         let ore_production = ProductionModule::new(
             "PowerToOre".to_string(),
-            vec![Amount::new(Product::PowerCells, 1)],
-            vec![Amount::new(Product::Ores, 2)],
+            vec![Amount::new(Product::ware("PowerCells"), 1)],
+            vec![Amount::new(Product::ware("Ores"), 2)],
             1,
             0,
         );
         let metal_production = ProductionModule::new(
             "OreAndEnergyToMetal".to_string(),
-            vec![Amount::new(Product::PowerCells, 2), Amount::new(Product::Ores, 4)],
-            vec![Amount::new(Product::Metals, 1)],
+            vec![Amount::new(Product::ware("PowerCells"), 2), Amount::new(Product::ware("Ores"), 4)],
+            vec![Amount::new(Product::ware("Metals"), 1)],
             3,
             0,
         );
 
         assert_eq!(Ok(()), construct.install(Production(ore_production.clone())));
         assert_eq!(Ok(()), construct.install(Production(metal_production.clone())));
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(None, construct.current_storage.get(&Product::Ores));
-        assert_eq!(None, construct.current_storage.get(&Product::Metals));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Metals")));
 
-        assert_eq!(200, request_load(&mut construct, Amount::new(Product::PowerCells, 200)));
+        assert_eq!(200, request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 200)));
 
         next_turn(&mut construct, 1);
 
-        assert_eq!(Some(&199), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(None, construct.current_storage.get(&Product::Ores));
-        assert_eq!(None, construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&199), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 2);
 
-        assert_eq!(Some(&198), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&2), construct.current_storage.get(&Product::Ores));
-        assert_eq!(None, construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&198), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&2), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 3);
 
-        assert_eq!(Some(&195), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(None, construct.current_storage.get(&Product::Ores));
-        assert_eq!(None, construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&195), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 4);
 
-        assert_eq!(Some(&194), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&2), construct.current_storage.get(&Product::Ores));
-        assert_eq!(None, construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&194), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&2), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 5);
 
-        assert_eq!(Some(&193), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&4), construct.current_storage.get(&Product::Ores));
-        assert_eq!(None, construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&193), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&4), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 6);
 
-        assert_eq!(Some(&190), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&2), construct.current_storage.get(&Product::Ores));
-        assert_eq!(Some(&1), construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&190), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&2), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&1), construct.current_storage.get(&Product::ware("Metals")));
 
         for i in 7..200 {
             next_turn(&mut construct, i);
         }
 
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&80), construct.current_storage.get(&Product::Ores));
-        assert_eq!(Some(&40), construct.current_storage.get(&Product::Metals)); //(200-80/2)/4
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&80), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&40), construct.current_storage.get(&Product::ware("Metals"))); //(200-80/2)/4
 
         for i in 201..205 {
             next_turn(&mut construct, i);
         }
 
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&80), construct.current_storage.get(&Product::Ores));
-        assert_eq!(Some(&40), construct.current_storage.get(&Product::Metals));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&80), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&40), construct.current_storage.get(&Product::ware("Metals")));
 
         //Bigger output than input will fill up the station over time
         let metal_production = ProductionModule::new(
             "MetalToEnergy".to_string(),
-            vec![Amount::new(Product::Metals, 1)],
-            vec![Amount::new(Product::PowerCells, 200)],
+            vec![Amount::new(Product::ware("Metals"), 1)],
+            vec![Amount::new(Product::ware("PowerCells"), 200)],
             1,
             0,
         );
@@ -435,27 +992,207 @@ mod tests_int {
 
         next_turn(&mut construct, 206);
 
-        assert_eq!(None, construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&80), construct.current_storage.get(&Product::Ores));
-        assert_eq!(Some(&39), construct.current_storage.get(&Product::Metals));
+        assert_eq!(None, construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&80), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&39), construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 207);
 
-        assert_eq!(Some(&200), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&80), construct.current_storage.get(&Product::Ores));
-        assert_eq!(Some(&38), construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&200), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&80), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&38), construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 208);
 
-        assert_eq!(Some(&197), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&76), construct.current_storage.get(&Product::Ores));
-        assert_eq!(Some(&38), construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&197), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&76), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&38), construct.current_storage.get(&Product::ware("Metals")));
 
         next_turn(&mut construct, 209);
 
-        assert_eq!(Some(&196), construct.current_storage.get(&Product::PowerCells));
-        assert_eq!(Some(&78), construct.current_storage.get(&Product::Ores));
-        assert_eq!(Some(&38), construct.current_storage.get(&Product::Metals));
+        assert_eq!(Some(&196), construct.current_storage.get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&78), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&38), construct.current_storage.get(&Product::ware("Metals")));
+    }
+
+    #[test]
+    fn install_and_uninstall_module_cargo() {
+        let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
+        let mut construct = Construct::new("The base".to_string(), 15, sector_position);
+        let ore_production = ProductionModule::new("PowerToOre".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 2)], 1, 0);
+        let batteries = StorageModule::new("Batteries".to_string(), ProductCategory::Energy, 10);
+        request_load(&mut construct, Amount::new(Product::module(Production(ore_production.clone())), 1));
+
+        assert_eq!(ConstructEvenReturnType::Denied("No module named Nope in storage.".to_string()), install_module(&mut construct, "Nope"));
+        assert_eq!(ConstructEvenReturnType::ModuleInstalled, install_module(&mut construct, "PowerToOre"));
+        assert_eq!(Some(&Production(ore_production.clone())), construct.modules.first());
+        assert_eq!(0, construct.used_volume());
+
+        // Busy producing.
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 1));
+        next_turn(&mut construct, 1);
+        assert_eq!(ConstructEvenReturnType::Denied("Module PowerToOre is still producing.".to_string()), uninstall_module(&mut construct, "PowerToOre"));
+        next_turn(&mut construct, 2);
+        assert_eq!(Some(&2), construct.current_storage.get(&Product::ware("Ores")));
+
+        // The module itself needs 10 volume, and the ores take 2 of the 15.
+        request_load(&mut construct, Amount::new(Product::ware("Ores"), 4));
+        assert_eq!(ConstructEvenReturnType::Denied("No room to store module PowerToOre.".to_string()), uninstall_module(&mut construct, "PowerToOre"));
+        request_unload(&mut construct, Amount::new(Product::ware("Ores"), 4));
+        assert_eq!(ConstructEvenReturnType::ModuleUninstalled, uninstall_module(&mut construct, "PowerToOre"));
+        assert!(construct.modules.is_empty());
+        assert_eq!(12, construct.used_volume());
+        assert_eq!(ConstructEvenReturnType::Denied("No module named PowerToOre installed.".to_string()), uninstall_module(&mut construct, "PowerToOre"));
+
+        // A storage module cannot be removed while its hold is needed.
+        request_unload(&mut construct, Amount::new(Product::ware("Ores"), 2));
+        assert_eq!(Ok(()), construct.install(Storage(batteries)));
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 10));
+        assert_eq!(ConstructEvenReturnType::Denied("No room to store module Batteries.".to_string()), uninstall_module(&mut construct, "Batteries"));
+        request_unload(&mut construct, Amount::new(Product::ware("PowerCells"), 10));
+        assert_eq!(ConstructEvenReturnType::Denied("No room to store module Batteries.".to_string()), uninstall_module(&mut construct, "Batteries"));
+        // The stored module kept its production state, so it is not equal to the freshly created one.
+        construct.current_storage.retain(|product, _| !matches!(product, Product::Module(_)));
+        assert_eq!(ConstructEvenReturnType::ModuleUninstalled, uninstall_module(&mut construct, "Batteries"));
+
+        // Module cargo survives a save.
+        let saved = serde_json::to_string(&construct).unwrap();
+        assert_eq!(construct, serde_json::from_str::<Construct>(&saved).unwrap());
+    }
+
+    #[test]
+    fn saved_speed_is_at_least_one() {
+        let construct = Construct::new("Ship".to_string(), 100, ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0)).with_speed(3);
+        let saved = serde_json::to_string(&construct).unwrap();
+        assert_eq!(3, serde_json::from_str::<Construct>(&saved).unwrap().speed());
+
+        let stopped = saved.replace("\"speed\":3", "\"speed\":0");
+        assert_ne!(saved, stopped);
+        assert_eq!(1, serde_json::from_str::<Construct>(&stopped).unwrap().speed());
+        let without_speed = saved.replace(",\"speed\":3", "");
+        assert_ne!(saved, without_speed);
+        assert_eq!(1, serde_json::from_str::<Construct>(&without_speed).unwrap().speed());
+    }
+
+    #[test]
+    fn install_the_best_stored_module_under_a_new_name() {
+        let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
+        let mut construct = Construct::new("The base".to_string(), 30, sector_position);
+        let new_production = ProductionModule::new("PowerToOre".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 2)], 1, 0);
+        let mut worn_production = new_production.clone();
+        worn_production.wear_mut().record_cycle();
+        worn_production.wear_mut().apply(40);
+        request_load(&mut construct, Amount::new(Product::module(Production(worn_production.clone())), 1));
+        request_load(&mut construct, Amount::new(Product::module(Production(new_production.clone())), 1));
+
+        assert_eq!(ConstructEvenReturnType::ModuleInstalled, install_module(&mut construct, "PowerToOre"));
+        assert_eq!(Some(&Production(new_production)), construct.modules.first());
+        assert_eq!(ConstructEvenReturnType::Denied("Module with that name already exists.".to_string()), install_module(&mut construct, "PowerToOre"));
+
+        let installed = construct.handle_event(&ConstructEventType::External(ExternalConstructEventType::InstallModule { module_name: "PowerToOre".to_string(), installed_name: Some("SecondMine".to_string()), price: None }));
+        assert_eq!(ConstructEvenReturnType::ModuleInstalled, installed);
+        assert_eq!(60, production_module(&construct, "SecondMine").wear().condition());
+        assert!(!construct.current_storage.keys().any(|product| matches!(product, Product::Module(_))));
+    }
+
+    #[test]
+    fn production_stats_and_idle_reasons() {
+        let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
+        let mut construct = Construct::new("The base".to_string(), 3, sector_position);
+        let ore_production = ProductionModule::new("PowerToOre".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 2)], 2, 0);
+        assert_eq!(Ok(()), construct.install(Production(ore_production)));
+
+        next_turn(&mut construct, 1);
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 2));
+        next_turn(&mut construct, 2);
+        next_turn(&mut construct, 3);
+        next_turn(&mut construct, 4);
+        next_turn(&mut construct, 5);
+        // The second output of two ores does not fit next to the first two ores.
+        next_turn(&mut construct, 6);
+        next_turn(&mut construct, 7);
+        set_module_enabled(&mut construct, "PowerToOre", false);
+        next_turn(&mut construct, 8);
+
+        let stats = production_module(&construct, "PowerToOre").stats();
+        assert_eq!(1, stats.completed_cycles());
+        assert_eq!(&vec![Amount::new(Product::ware("Ores"), 2)], stats.produced());
+        assert_eq!(&vec![Amount::new(Product::ware("PowerCells"), 2)], stats.consumed());
+        assert_eq!(2, stats.producing_turns());
+        assert_eq!(1, stats.idle_turns_for(&IdleReason::MissingInput(Product::ware("PowerCells"))));
+        assert_eq!(2, stats.idle_turns_for(&IdleReason::NoRoomForOutput));
+        assert_eq!(1, stats.idle_turns_for(&IdleReason::Disabled));
+        assert_eq!(0, stats.idle_turns_for(&IdleReason::WaitingForTriggerTime));
+    }
+
+    #[test]
+    fn modules_compete_for_inputs() {
+        let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
+        let mut construct = Construct::new("The base".to_string(), 500, sector_position.clone());
+        let ore_production = ProductionModule::new("PowerToOre".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 1)], 1, 0);
+        let metal_production = ProductionModule::new("PowerToMetal".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Metals"), 1)], 1, 0);
+        assert_eq!(Ok(()), construct.install(Production(ore_production)));
+        assert_eq!(Ok(()), construct.install(Production(metal_production)));
+
+        assert_eq!(ConstructEvenReturnType::ModuleUpdated, set_module_priority(&mut construct, "PowerToMetal", 1));
+        assert_eq!(ConstructEvenReturnType::Denied("No production module named Nope installed.".to_string()), set_module_priority(&mut construct, "Nope", 1));
+
+        // Priority, the metal production always goes first.
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 1));
+        next_turn(&mut construct, 1);
+        assert!(!production_module(&construct, "PowerToOre").stored_input());
+        assert!(production_module(&construct, "PowerToMetal").stored_input());
+
+        // Fair share, the modules take turns going first.
+        assert_eq!(ConstructEvenReturnType::AllocationModeSet, construct.handle_event(&ConstructEventType::External(ExternalConstructEventType::SetAllocationMode(AllocationMode::FairShare))));
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 1));
+        next_turn(&mut construct, 2);
+        assert!(production_module(&construct, "PowerToOre").stored_input());
+        assert!(!production_module(&construct, "PowerToMetal").stored_input());
+
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 1));
+        next_turn(&mut construct, 3);
+        assert!(!production_module(&construct, "PowerToOre").stored_input());
+        assert!(production_module(&construct, "PowerToMetal").stored_input());
+        assert_eq!(Some(&1), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&1), construct.current_storage.get(&Product::ware("Metals")));
+
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 1));
+        next_turn(&mut construct, 4);
+        assert!(production_module(&construct, "PowerToOre").stored_input());
+
+        // A disabled module keeps its input and does not produce until it is enabled again.
+        assert_eq!(ConstructEvenReturnType::ModuleUpdated, set_module_enabled(&mut construct, "PowerToOre", false));
+        request_load(&mut construct, Amount::new(Product::ware("PowerCells"), 1));
+        next_turn(&mut construct, 5);
+        assert!(production_module(&construct, "PowerToOre").stored_input());
+        assert_eq!(Some(&1), construct.current_storage.get(&Product::ware("Ores")));
+        assert_eq!(Some(&2), construct.current_storage.get(&Product::ware("Metals")));
+
+        assert_eq!(ConstructEvenReturnType::ModuleUpdated, set_module_enabled(&mut construct, "PowerToOre", true));
+        next_turn(&mut construct, 6);
+        assert!(!production_module(&construct, "PowerToOre").stored_input());
+        assert_eq!(Some(&2), construct.current_storage.get(&Product::ware("Ores")));
+    }
+
+    fn install_module(construct: &mut Construct, module_name: &str) -> ConstructEvenReturnType {
+        construct.handle_event(&ConstructEventType::External(ExternalConstructEventType::InstallModule { module_name: module_name.to_string(), installed_name: None, price: None }))
+    }
+
+    fn uninstall_module(construct: &mut Construct, module_name: &str) -> ConstructEvenReturnType {
+        construct.handle_event(&ConstructEventType::External(ExternalConstructEventType::UninstallModule { module_name: module_name.to_string() }))
+    }
+
+    fn set_module_priority(construct: &mut Construct, module_name: &str, priority: u32) -> ConstructEvenReturnType {
+        construct.handle_event(&ConstructEventType::External(ExternalConstructEventType::SetModulePriority { module_name: module_name.to_string(), priority }))
+    }
+
+    fn set_module_enabled(construct: &mut Construct, module_name: &str, enabled: bool) -> ConstructEvenReturnType {
+        construct.handle_event(&ConstructEventType::External(ExternalConstructEventType::SetModuleEnabled { module_name: module_name.to_string(), enabled }))
+    }
+
+    fn production_module<'a>(construct: &'a Construct, module_name: &str) -> &'a ProductionModule {
+        construct.production_module(module_name).unwrap()
     }
 
     fn request_load(construct: &mut Construct, amount: Amount) -> u32 {
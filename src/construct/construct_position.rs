@@ -10,6 +10,8 @@ use crate::sector::{InternalSectorEventType, SectorEvenReturnType, SectorEventTy
 pub enum ConstructPositionStatus {
     IsDocked(String),
     InSector(ConstructPositionSector),
+    // Between sectors, the constructs docked at it travel along. Enters the group of the target sector on arrival.
+    InTransit { from: SectorPosition, to: SectorPosition, group_address: Option<usize>, arrival_turn: u64 },
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -42,6 +44,7 @@ pub enum ExternalConstructPositionEventType {
     Dock(String),
     Undock,
     EnterSector(ConstructPositionSector),
+    Depart { from: SectorPosition, to: SectorPosition, group_address: Option<usize>, arrival_turn: u64 },
     //TODO there should just be one move external method and the rest should be internal
     EnterGroup(usize),
 }
@@ -88,13 +91,18 @@ impl ConstructPositionState {
                 self.position = InSector(sector_position.clone());
                 RequestProcessed
             }
+            ConstructPositionEventType::External(ExternalConstructPositionEventType::Depart { from, to, group_address, arrival_turn }) => {
+                self.position = ConstructPositionStatus::InTransit { from: from.clone(), to: to.clone(), group_address: *group_address, arrival_turn: *arrival_turn };
+                RequestProcessed
+            }
             ConstructPositionEventType::External(ExternalConstructPositionEventType::EnterGroup(group_address)) => {
                 match self.position() {
                     ConstructPositionStatus::InSector(current_position) => {
                         self.position = InSector(ConstructPositionSector::new(current_position.sector_position.clone(), group_address.clone()));
                         RequestProcessed
                     }
-                    ConstructPositionStatus::IsDocked(docked_at) => Denied(format!("Currently docket at {} so cannot dock another place.", docked_at))
+                    ConstructPositionStatus::IsDocked(docked_at) => Denied(format!("Currently docket at {} so cannot dock another place.", docked_at)),
+                    ConstructPositionStatus::InTransit { to, .. } => Denied(format!("Currently in transit to {:?} so cannot enter a group.", to))
                 }
             }
             ConstructPositionEventType::Internal(InternalConstructPositionEventType::Undock(sector_position)) => {
@@ -126,6 +134,12 @@ pub struct DockerModule {
     docked_construct: Option<String>,
 }
 
+impl Default for DockerModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DockerModule {
     pub fn new() -> Self {
         DockerModule { docked_construct: None }
@@ -169,7 +183,8 @@ impl MyLittleUniverse {
             Some(source_construct) => {
                 match &source_construct.position.position {
                     IsDocked(docked_at_name) => return ConstructPositionEventReturnType::Denied(format!("Construct {} is already docked at {} so cannot dock again. Use Undock first.", source_construct_name, docked_at_name)),
-                    InSector(position) => (source_construct.clone(), position.clone())
+                    InSector(position) => (source_construct.clone(), position.clone()),
+                    ConstructPositionStatus::InTransit { to, .. } => return ConstructPositionEventReturnType::Denied(format!("Construct {} is in transit to {:?} and cannot dock.", source_construct_name, to))
                 }
             }
         };
@@ -221,7 +236,7 @@ impl MyLittleUniverse {
                 ConstructPositionStatus::IsDocked(docker_construct_name) => {
                     self.construct_is_part_of_docker_parents(docker_construct_name.clone(), query_construct_name)
                 }
-                ConstructPositionStatus::InSector(_) => return false
+                ConstructPositionStatus::InSector(_) | ConstructPositionStatus::InTransit { .. } => false
             }
         }
     }
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::amount::Amount;
+use crate::construct::construct::unload;
+use crate::products::Product;
+use crate::wear::ModuleWear;
+
+// Produces power every turn it has its fuel, power cannot be stored.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct GeneratorModule {
+    name: String,
+    power_per_turn: u32,
+    #[serde(default)]
+    fuel: Vec<Amount>,
+    #[serde(default)]
+    wear: ModuleWear,
+}
+
+impl GeneratorModule {
+    pub fn new(name: String, power_per_turn: u32, fuel: Vec<Amount>) -> Self {
+        GeneratorModule { name, power_per_turn, fuel, wear: ModuleWear::default() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn power_per_turn(&self) -> u32 {
+        self.power_per_turn
+    }
+    pub fn fuel(&self) -> &Vec<Amount> {
+        &self.fuel
+    }
+    pub fn wear(&self) -> &ModuleWear {
+        &self.wear
+    }
+    pub fn wear_mut(&mut self) -> &mut ModuleWear {
+        &mut self.wear
+    }
+
+    pub fn generate(&mut self, current_storage: &mut HashMap<Product, u32>) -> u32 {
+        if self.wear.is_broken_down() {
+            return 0;
+        }
+        let has_fuel = self.fuel.iter()
+            .all(|amount| current_storage.get(amount.product()).unwrap_or(&0) >= &amount.amount());
+        if !has_fuel {
+            return 0;
+        }
+        for amount in &self.fuel {
+            unload(current_storage, amount);
+        }
+        self.wear.record_cycle();
+        self.power_per_turn * self.wear.efficiency_percent() / 100
+    }
+}
@@ -0,0 +1,150 @@
+use std::cmp::min;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::construct_position::ConstructPositionStatus;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::Product;
+use crate::wear::ModuleWear;
+
+// Extracts a ware from the deposits in the group of its construct, every turn there is room for it.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct MiningModule {
+    name: String,
+    // A ware id, modules cannot be mined.
+    product: String,
+    extraction_per_turn: u32,
+    #[serde(default)]
+    tracked_turns: u64,
+    #[serde(default)]
+    mined: u64,
+    #[serde(default)]
+    wear: ModuleWear,
+}
+
+impl MiningModule {
+    pub fn new(name: String, product: String, extraction_per_turn: u32) -> Self {
+        assert!(extraction_per_turn > 0, "Mining module {} needs an extraction_per_turn above zero.", name);
+        MiningModule { name, product, extraction_per_turn, tracked_turns: 0, mined: 0, wear: ModuleWear::default() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn product(&self) -> Product {
+        Product::ware(&self.product)
+    }
+    pub fn extraction_per_turn(&self) -> u32 {
+        self.extraction_per_turn
+    }
+    pub fn tracked_turns(&self) -> u64 {
+        self.tracked_turns
+    }
+    pub fn mined(&self) -> u64 {
+        self.mined
+    }
+    pub fn wear(&self) -> &ModuleWear {
+        &self.wear
+    }
+    pub fn wear_mut(&mut self) -> &mut ModuleWear {
+        &mut self.wear
+    }
+
+    // At most the room that is left in storage, a worn module extracts less.
+    pub fn extraction(&self, room: u32) -> u32 {
+        if self.wear.is_broken_down() {
+            return 0;
+        }
+        min(room, self.extraction_per_turn * self.wear.efficiency_percent() / 100)
+    }
+
+    pub fn record_turn(&mut self, mined: u32) {
+        self.tracked_turns += 1;
+        self.mined += u64::from(mined);
+        if mined > 0 {
+            self.wear.record_cycle();
+        }
+    }
+}
+
+impl MyLittleUniverse {
+    // Docked constructs do not mine, the deposits regenerate after all constructs took their share.
+    pub(crate) fn mining_phase(&mut self, _current_turn: &u64) {
+        for construct_name in self.construct_names_in_turn_order() {
+            let construct = self.constructs.get_mut(&construct_name).unwrap();
+            if let ConstructPositionStatus::InSector(position) = construct.position().position() {
+                let group_address = position.group_address();
+                if let Some(sector) = self.sectors.get_mut(position.sector_position()) {
+                    construct.mine(sector, group_address);
+                }
+            }
+        }
+        for sector in self.sectors.values_mut() {
+            sector.regenerate_deposits();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::construct::construct_position::ExternalConstructPositionEventType;
+    use crate::construct::construct::ExternalConstructEventType;
+    use crate::construct::mining_module::MiningModule;
+    use crate::construct_module::ConstructModuleType::Mining;
+    use crate::ExternalCommands;
+    use crate::my_little_universe::{ExternalUniverseEventType, OfMove};
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::sector::{ResourceDeposit, SectorPosition};
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::{add_distant_sector, generate_simple_universe};
+
+    #[test]
+    fn mining_empties_the_deposit_of_the_group() {
+        let mut universe = generate_simple_universe("mining".to_string(), &read_recipe_catalog("default"));
+        let sector = universe.sectors.get_mut(&SectorPosition::new(1, 1, 1)).unwrap();
+        sector.add_deposit(ResourceDeposit::new(0, Product::ware("Metals"), 25).with_regeneration(1, 30));
+        let transport = universe.constructs.get_mut("transport").unwrap();
+        assert_eq!(Ok(()), transport.install(Mining(MiningModule::new("Drill".to_string(), "Metals".to_string(), 10))));
+        assert_eq!(Ok(()), transport.install(Mining(MiningModule::new("IceDrill".to_string(), "Ice".to_string(), 10))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        // Ten per turn, the deposit regenerates one per turn. The simple universe already has an ore deposit in the group.
+        for _ in 0..3 {
+            assert!(universe.request_execute_turn());
+        }
+        let transport = universe.constructs().get("transport").unwrap();
+        assert_eq!(Some(&27), transport.current_storage().get(&Product::ware("Metals")));
+        assert_eq!(None, transport.current_storage().get(&Product::ware("Ice")));
+        assert_eq!(1, universe.sectors.get(&SectorPosition::new(1, 1, 1)).unwrap().deposits()[1].amount());
+
+        // Docked constructs do not mine.
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+        assert!(universe.request_execute_turn());
+        assert_eq!(Some(&27), universe.constructs().get("transport").unwrap().current_storage().get(&Product::ware("Metals")));
+        assert_eq!(2, universe.sectors.get(&SectorPosition::new(1, 1, 1)).unwrap().deposits()[1].amount());
+    }
+
+    #[test]
+    fn constructs_in_transit_do_not_mine() {
+        let mut universe = generate_simple_universe("mining".to_string(), &read_recipe_catalog("default"));
+        let distant = add_distant_sector(&mut universe);
+        universe.sectors.get_mut(&distant).unwrap().add_deposit(ResourceDeposit::new(0, Product::ware("Ores"), 100));
+        let transport = universe.constructs.get_mut("transport").unwrap();
+        assert_eq!(Ok(()), transport.install(Mining(MiningModule::new("Drill".to_string(), "Ores".to_string(), 10))));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), distant.clone(), Some(0)))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        // Four turns on the way, the construct enters the sector after the production phase of the arrival turn.
+        for _ in 0..4 {
+            assert!(universe.request_execute_turn());
+            assert_eq!(None, universe.constructs().get("transport").unwrap().current_storage().get(&Product::ware("Ores")));
+        }
+        assert!(universe.request_execute_turn());
+        assert_eq!(Some(&10), universe.constructs().get("transport").unwrap().current_storage().get(&Product::ware("Ores")));
+        assert_eq!(90, universe.sectors.get(&distant).unwrap().deposits()[0].amount());
+    }
+}
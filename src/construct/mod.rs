@@ -1,4 +1,7 @@
 pub mod production_module;
 pub mod construct;
 pub mod amount;
-pub mod construct_position;
\ No newline at end of file
+pub mod construct_position;pub mod storage_module;
+pub mod shipyard_module;
+pub mod generator_module;
+pub mod mining_module;
@@ -1,17 +1,200 @@
+use std::cmp::max;
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::construct::amount::Amount;
 use crate::construct::construct::Construct;
+use crate::module_levels::ModuleLevel;
+use crate::products::Product;
+use crate::recipes::{Recipe, RecipeCatalog};
+use crate::wear::ModuleWear;
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
 pub struct ProductionModule {
     name: String,
+    #[serde(default)]
+    recipe_id: Option<String>,
     input: Vec<Amount>,
     output: Vec<Amount>,
     production_time: u32,
     production_trigger_time: u64,
     stored_input: bool,
     stored_output: bool,
+    #[serde(default)]
+    priority: u32,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+    #[serde(default = "first_level")]
+    level: u32,
+    #[serde(default = "full_percent")]
+    output_percent: u32,
+    #[serde(default = "full_percent")]
+    production_time_percent: u32,
+    #[serde(default)]
+    stats: ProductionStats,
+    #[serde(default)]
+    orders: Vec<ProductionOrder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    own_recipe: Option<OwnRecipe>,
+    #[serde(default)]
+    power_draw: u32,
+    #[serde(default)]
+    wear: ModuleWear,
+}
+
+// What the module produces without orders, kept while its orders run other recipes.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+struct OwnRecipe {
+    recipe_id: Option<String>,
+    input: Vec<Amount>,
+    output: Vec<Amount>,
+    production_time: u32,
+    power_draw: u32,
+}
+
+impl OwnRecipe {
+    fn from_recipe(recipe: &Recipe) -> Self {
+        OwnRecipe { recipe_id: Some(recipe.id().to_string()), input: recipe.input().clone(), output: recipe.output().clone(), production_time: recipe.production_time(), power_draw: recipe.power_draw() }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum OrderTarget {
+    Batches(u32),
+    UntilStock(Amount),
+}
+
+// The first order in the queue is the active one, its recipe is used by the module until the target is reached.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct ProductionOrder {
+    recipe: Recipe,
+    target: OrderTarget,
+    completed_batches: u32,
+}
+
+impl ProductionOrder {
+    pub fn new(recipe: Recipe, target: OrderTarget) -> Self {
+        ProductionOrder { recipe, target, completed_batches: 0 }
+    }
+
+    pub fn recipe(&self) -> &Recipe {
+        &self.recipe
+    }
+    pub fn target(&self) -> &OrderTarget {
+        &self.target
+    }
+    pub fn completed_batches(&self) -> u32 {
+        self.completed_batches
+    }
+
+    fn is_finished(&self, current_storage: &HashMap<Product, u32>) -> bool {
+        match &self.target {
+            OrderTarget::Batches(batches) => self.completed_batches >= *batches,
+            OrderTarget::UntilStock(amount) => current_storage.get(amount.product()).unwrap_or(&0) >= &amount.amount(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum IdleReason {
+    MissingInput(Product),
+    NoRoomForOutput,
+    WaitingForTriggerTime,
+    Disabled,
+    NoPower,
+    BrokenDown,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct IdleTurns {
+    reason: IdleReason,
+    turns: u64,
+}
+
+impl IdleTurns {
+    pub fn reason(&self) -> &IdleReason {
+        &self.reason
+    }
+    pub fn turns(&self) -> u64 {
+        self.turns
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash, Default)]
+pub struct ProductionStats {
+    tracked_turns: u64,
+    completed_cycles: u64,
+    produced: Vec<Amount>,
+    consumed: Vec<Amount>,
+    producing_turns: u64,
+    idle_turns: Vec<IdleTurns>,
+}
+
+impl ProductionStats {
+    pub fn tracked_turns(&self) -> u64 {
+        self.tracked_turns
+    }
+    pub fn completed_cycles(&self) -> u64 {
+        self.completed_cycles
+    }
+    pub fn produced(&self) -> &Vec<Amount> {
+        &self.produced
+    }
+    pub fn consumed(&self) -> &Vec<Amount> {
+        &self.consumed
+    }
+    pub fn producing_turns(&self) -> u64 {
+        self.producing_turns
+    }
+    pub fn idle_turns(&self) -> &Vec<IdleTurns> {
+        &self.idle_turns
+    }
+
+    pub fn idle_turns_for(&self, reason: &IdleReason) -> u64 {
+        self.idle_turns.iter()
+            .find(|idle_turns| idle_turns.reason.eq(reason))
+            .map_or(0, |idle_turns| idle_turns.turns)
+    }
+
+    pub fn add(&mut self, other: &ProductionStats) {
+        self.tracked_turns += other.tracked_turns;
+        self.completed_cycles += other.completed_cycles;
+        add_amounts(&mut self.produced, &other.produced);
+        add_amounts(&mut self.consumed, &other.consumed);
+        self.producing_turns += other.producing_turns;
+        for idle_turns in &other.idle_turns {
+            self.add_idle_turns(&idle_turns.reason, idle_turns.turns);
+        }
+    }
+
+    fn add_idle_turns(&mut self, reason: &IdleReason, turns: u64) {
+        match self.idle_turns.iter_mut().find(|idle_turns| idle_turns.reason.eq(reason)) {
+            Some(idle_turns) => idle_turns.turns += turns,
+            None => self.idle_turns.push(IdleTurns { reason: reason.clone(), turns }),
+        }
+    }
+}
+
+fn add_amounts(totals: &mut Vec<Amount>, amounts: &Vec<Amount>) {
+    for amount in amounts {
+        match totals.iter_mut().find(|total| total.product().eq(amount.product())) {
+            Some(total) => *total = Amount::new(amount.product().clone(), total.amount() + amount.amount()),
+            None => totals.push(amount.clone()),
+        }
+    }
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn first_level() -> u32 {
+    1
+}
+
+fn full_percent() -> u32 {
+    100
 }
 
 impl ProductionModule {
@@ -42,20 +225,178 @@ impl ProductionModule {
     }
 
     fn have_room_for_outputs(&mut self, construct: &Construct) -> bool {
-        let mut total_need = 0;
-        for output in &self.output {
-            total_need += output.amount();
-        }
-        return total_need + construct.current_storage().values().sum::<u32>() <= construct.capacity();
+        construct.has_room_for(&self.leveled_output())
     }
 
-    pub fn will_output(&self, current_turn: &u64) -> Option<&Vec<Amount>> {
+    pub fn will_output(&self, current_turn: &u64) -> Option<Vec<Amount>> {
         if self.stored_output || (self.stored_input && current_turn >= &self.production_trigger_time) {
-            return Some(self.output());
+            return Some(self.leveled_output());
         }
         None
     }
 
+    // The output and production time are the level 1 values, the level scales them.
+    pub fn leveled_output(&self) -> Vec<Amount> {
+        self.output.iter()
+            .map(|amount| Amount::new(amount.product().clone(), max(1, amount.amount() * self.output_percent / 100)))
+            .collect()
+    }
+
+    // Wear slows the module down, rounded so short cycles only slow down once the module is well worn.
+    pub fn leveled_production_time(&self) -> u32 {
+        let efficiency_percent = self.wear.efficiency_percent();
+        max(1, (self.production_time * self.production_time_percent + efficiency_percent / 2) / efficiency_percent)
+    }
+
+    pub fn stats(&self) -> &ProductionStats {
+        &self.stats
+    }
+
+    pub fn orders(&self) -> &Vec<ProductionOrder> {
+        &self.orders
+    }
+
+    // A module without orders produces its own recipe for ever. Orders only run while the module is enabled,
+    // a module that finished its last order goes back to its own recipe and stops by disabling itself.
+    pub fn add_order(&mut self, order: ProductionOrder) {
+        self.orders.push(order);
+    }
+
+    // A module whose last order is removed goes back to its own recipe, once the batch it is on is done.
+    pub fn remove_order(&mut self, index: usize) -> Result<ProductionOrder, String> {
+        if index >= self.orders.len() {
+            return Err(format!("Module {} has no order {}, it has {} orders.", self.name, index, self.orders.len()));
+        }
+        let order = self.orders.remove(index);
+        if self.orders.is_empty() && !self.stored_input && !self.stored_output {
+            self.restore_own_recipe();
+        }
+        Ok(order)
+    }
+
+    // Only switches recipe between batches, so a batch always finishes with the recipe it started with.
+    pub fn advance_orders(&mut self, current_storage: &HashMap<Product, u32>) {
+        if self.stored_input || self.stored_output {
+            return;
+        }
+        let had_orders = !self.orders.is_empty();
+        while self.orders.first().is_some_and(|order| order.is_finished(current_storage)) {
+            self.orders.remove(0);
+        }
+        match self.orders.first() {
+            Some(order) => {
+                if self.own_recipe.is_none() {
+                    self.own_recipe = Some(OwnRecipe { recipe_id: self.recipe_id.clone(), input: self.input.clone(), output: self.output.clone(), production_time: self.production_time, power_draw: self.power_draw });
+                }
+                if self.recipe_id.as_deref() != Some(order.recipe.id()) {
+                    let recipe = order.recipe.clone();
+                    self.use_recipe(&recipe);
+                }
+            }
+            None => {
+                self.restore_own_recipe();
+                if had_orders {
+                    self.enabled = false;
+                }
+            }
+        }
+    }
+
+    fn restore_own_recipe(&mut self) {
+        if let Some(own_recipe) = self.own_recipe.take() {
+            self.recipe_id = own_recipe.recipe_id;
+            self.input = own_recipe.input;
+            self.output = own_recipe.output;
+            self.production_time = own_recipe.production_time;
+            self.power_draw = own_recipe.power_draw;
+        }
+    }
+
+    fn use_recipe(&mut self, recipe: &Recipe) {
+        self.recipe_id = Some(recipe.id().to_string());
+        self.input = recipe.input().clone();
+        self.output = recipe.output().clone();
+        self.production_time = recipe.production_time();
+        self.power_draw = recipe.power_draw();
+    }
+
+    // Saved modules keep a copy of their recipes, this picks up the recipes as they are in the catalog now.
+    pub fn refresh_recipes(&mut self, recipe_catalog: &RecipeCatalog) {
+        if let Some(recipe) = self.recipe_id.as_deref().and_then(|recipe_id| recipe_catalog.get(recipe_id)) {
+            let recipe = recipe.clone();
+            self.use_recipe(&recipe);
+        }
+        if let Some(own_recipe) = &mut self.own_recipe {
+            if let Some(recipe) = own_recipe.recipe_id.as_deref().and_then(|recipe_id| recipe_catalog.get(recipe_id)) {
+                *own_recipe = OwnRecipe::from_recipe(recipe);
+            }
+        }
+        for order in &mut self.orders {
+            if let Some(recipe) = recipe_catalog.get(order.recipe.id()) {
+                order.recipe = recipe.clone();
+            }
+        }
+    }
+
+    pub fn record_output(&mut self, amounts: &Vec<Amount>) {
+        if let Some(order) = self.orders.first_mut() {
+            order.completed_batches += 1;
+        }
+        self.stats.completed_cycles += 1;
+        self.wear.record_cycle();
+        add_amounts(&mut self.stats.produced, amounts);
+    }
+
+    pub fn record_input(&mut self, amounts: &Vec<Amount>) {
+        add_amounts(&mut self.stats.consumed, amounts);
+    }
+
+    // Called at the end of a turn, a turn where the module took input or stored output is not idle.
+    pub fn record_turn(&mut self, current_storage: &HashMap<Product, u32>, current_turn: &u64, was_active: bool) {
+        self.stats.tracked_turns += 1;
+        if was_active {
+            return;
+        }
+        let reason = if !self.enabled {
+            IdleReason::Disabled
+        } else if self.stored_output {
+            IdleReason::NoRoomForOutput
+        } else if self.stored_input {
+            self.stats.producing_turns += 1;
+            return;
+        } else if *current_turn < self.production_trigger_time {
+            IdleReason::WaitingForTriggerTime
+        } else {
+            match self.input.iter().find(|amount| current_storage.get(amount.product()).unwrap_or(&0) < &amount.amount()) {
+                Some(amount) => IdleReason::MissingInput(amount.product().clone()),
+                None => IdleReason::WaitingForTriggerTime,
+            }
+        };
+        self.stats.add_idle_turns(&reason, 1);
+    }
+
+    // For turns where the construct held the module back, see stall.
+    pub fn record_stalled_turn(&mut self, reason: &IdleReason) {
+        self.stats.tracked_turns += 1;
+        self.stats.add_idle_turns(reason, 1);
+    }
+
+    // Power is drawn while a batch is running, taking input and storing output does not need it.
+    pub fn needs_power(&self) -> bool {
+        self.power_draw > 0 && self.enabled && self.stored_input && !self.stored_output
+    }
+
+    // Pushes the end of the running batch one turn back.
+    pub fn stall(&mut self, current_turn: &u64) {
+        self.production_trigger_time = max(self.production_trigger_time, *current_turn) + 1;
+    }
+
+    pub fn upgrade(&mut self, module_level: &ModuleLevel) {
+        self.level = module_level.level();
+        self.output_percent = module_level.output_percent();
+        self.production_time_percent = module_level.production_time_percent();
+    }
+
     pub fn require_input(&self, current_turn: &u64) -> Option<&Vec<Amount>> {
         if current_turn >= &self.production_trigger_time && !self.stored_input {
             return Some(self.input());
@@ -66,13 +407,13 @@ impl ProductionModule {
     pub fn handle_turn(&mut self, current_turn: &u64) {
         if self.production_trigger_time <= *current_turn {
             if self.stored_input && !self.stored_output {
-                self.production_trigger_time = current_turn + u64::from(self.production_time);
+                self.production_trigger_time = current_turn + u64::from(self.leveled_production_time());
             }
         }
     }
 
     fn add_all_outputs(&mut self, construct: &mut Construct) {
-        for output in &mut self.output {
+        for output in &self.leveled_output() {
             let moved_amount = construct.load_request(&Amount::new(output.product().clone(), output.amount()));
 
             if moved_amount != output.amount() {
@@ -82,12 +423,24 @@ impl ProductionModule {
     }
 
     pub fn new(name: String, input: Vec<Amount>, output: Vec<Amount>, production_time: u32, production_trigger_time: u64) -> Self {
-        Self { name, input, output, production_time, production_trigger_time, stored_input: false, stored_output: false }
+        Self { name, recipe_id: None, input, output, production_time, production_trigger_time, stored_input: false, stored_output: false, priority: 0, enabled: true, level: first_level(), output_percent: full_percent(), production_time_percent: full_percent(), stats: ProductionStats::default(), orders: Vec::new(), own_recipe: None, power_draw: 0, wear: ModuleWear::default() }
+    }
+
+    pub fn from_recipe(name: String, recipe: &Recipe, production_trigger_time: u64) -> Self {
+        let mut production_module = Self::new(name, Vec::new(), Vec::new(), 0, production_trigger_time);
+        production_module.use_recipe(recipe);
+        production_module
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn recipe_id(&self) -> &Option<String> {
+        &self.recipe_id
+    }
     pub fn input(&self) -> &Vec<Amount> {
         &self.input
     }
@@ -108,6 +461,33 @@ impl ProductionModule {
         self.stored_output
     }
 
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+    pub fn power_draw(&self) -> u32 {
+        self.power_draw
+    }
+    pub fn wear(&self) -> &ModuleWear {
+        &self.wear
+    }
+    pub fn wear_mut(&mut self) -> &mut ModuleWear {
+        &mut self.wear
+    }
+
+    pub fn set_priority(&mut self, priority: u32) {
+        self.priority = priority;
+    }
+    // A disabled module keeps whatever input it already took until it is enabled again.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
     pub fn set_stored_input(&mut self, stored_input: bool) {
         self.stored_input = stored_input;
     }
@@ -122,7 +502,7 @@ impl ProductionModule {
             }
             if self.have_all_inputs(&construct) {
                 self.subtract_all_inputs(construct);
-                self.production_trigger_time = current_turn + self.production_time as u64;
+                self.production_trigger_time = current_turn + self.leveled_production_time() as u64;
             }
         }
     }
@@ -133,8 +513,11 @@ mod tests_int {
     use crate::construct::amount::Amount;
     use crate::construct::construct::Construct;
     use crate::construct::construct_position::ConstructPositionSector;
-    use crate::construct::production_module::ProductionModule;
+    use std::collections::HashMap;
+
+    use crate::construct::production_module::{OrderTarget, ProductionModule, ProductionOrder};
     use crate::products::Product;
+    use crate::recipes::Recipe;
     use crate::sector::SectorPosition;
 
     #[test]
@@ -144,20 +527,20 @@ mod tests_int {
 
         let mut ore_production = ProductionModule::new(
             "PowerToOre".to_string(),
-            vec![Amount::new(Product::PowerCells, 1)],
-            vec![Amount::new(Product::Ores, 2)],
+            vec![Amount::new(Product::ware("PowerCells"), 1)],
+            vec![Amount::new(Product::ware("Ores"), 2)],
             1,
             0,
         );
         let mut metal_production = ProductionModule::new(
             "OreAndEnergyToMetal".to_string(),
-            vec![Amount::new(Product::PowerCells, 2), Amount::new(Product::Ores, 4)],
-            vec![Amount::new(Product::Metals, 1)],
+            vec![Amount::new(Product::ware("PowerCells"), 2), Amount::new(Product::ware("Ores"), 4)],
+            vec![Amount::new(Product::ware("Metals"), 1)],
             3,
             0,
         );
 
-        construct.load_request(&Amount::new(Product::PowerCells, 200));
+        construct.load_request(&Amount::new(Product::ware("PowerCells"), 200));
 
         ore_production.next_turn(&1, &mut construct);
         metal_production.next_turn(&1, &mut construct);
@@ -165,9 +548,9 @@ mod tests_int {
         assert_eq!(2, ore_production.production_trigger_time);
         assert_eq!(0, metal_production.production_trigger_time);
 
-        assert_eq!(199, *construct.current_storage().get(&Product::PowerCells).unwrap());
-        assert_eq!(None, construct.current_storage().get(&Product::Ores));
-        assert_eq!(None, construct.current_storage().get(&Product::Metals));
+        assert_eq!(199, *construct.current_storage().get(&Product::ware("PowerCells")).unwrap());
+        assert_eq!(None, construct.current_storage().get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage().get(&Product::ware("Metals")));
 
         ore_production.next_turn(&2, &mut construct);
         metal_production.next_turn(&2, &mut construct);
@@ -175,9 +558,9 @@ mod tests_int {
         assert_eq!(3, ore_production.production_trigger_time);
         assert_eq!(0, metal_production.production_trigger_time);
 
-        assert_eq!(198, *construct.current_storage().get(&Product::PowerCells).unwrap());
-        assert_eq!(2, *construct.current_storage().get(&Product::Ores).unwrap());
-        assert_eq!(None, construct.current_storage().get(&Product::Metals));
+        assert_eq!(198, *construct.current_storage().get(&Product::ware("PowerCells")).unwrap());
+        assert_eq!(2, *construct.current_storage().get(&Product::ware("Ores")).unwrap());
+        assert_eq!(None, construct.current_storage().get(&Product::ware("Metals")));
 
         ore_production.next_turn(&3, &mut construct);
         metal_production.next_turn(&3, &mut construct);
@@ -185,9 +568,9 @@ mod tests_int {
         assert_eq!(4, ore_production.production_trigger_time);
         assert_eq!(6, metal_production.production_trigger_time);
 
-        assert_eq!(195, *construct.current_storage().get(&Product::PowerCells).unwrap());
-        assert_eq!(None, construct.current_storage().get(&Product::Ores));
-        assert_eq!(None, construct.current_storage().get(&Product::Metals));
+        assert_eq!(195, *construct.current_storage().get(&Product::ware("PowerCells")).unwrap());
+        assert_eq!(None, construct.current_storage().get(&Product::ware("Ores")));
+        assert_eq!(None, construct.current_storage().get(&Product::ware("Metals")));
 
         ore_production.next_turn(&4, &mut construct);
         metal_production.next_turn(&4, &mut construct);
@@ -195,9 +578,9 @@ mod tests_int {
         assert_eq!(5, ore_production.production_trigger_time);
         assert_eq!(6, metal_production.production_trigger_time);
 
-        assert_eq!(194, *construct.current_storage().get(&Product::PowerCells).unwrap());
-        assert_eq!(2, *construct.current_storage().get(&Product::Ores).unwrap());
-        assert_eq!(None, construct.current_storage().get(&Product::Metals));
+        assert_eq!(194, *construct.current_storage().get(&Product::ware("PowerCells")).unwrap());
+        assert_eq!(2, *construct.current_storage().get(&Product::ware("Ores")).unwrap());
+        assert_eq!(None, construct.current_storage().get(&Product::ware("Metals")));
 
 
         ore_production.next_turn(&5, &mut construct);
@@ -206,9 +589,9 @@ mod tests_int {
         assert_eq!(6, ore_production.production_trigger_time);
         assert_eq!(6, metal_production.production_trigger_time);
 
-        assert_eq!(193, *construct.current_storage().get(&Product::PowerCells).unwrap());
-        assert_eq!(4, *construct.current_storage().get(&Product::Ores).unwrap());
-        assert_eq!(None, construct.current_storage().get(&Product::Metals));
+        assert_eq!(193, *construct.current_storage().get(&Product::ware("PowerCells")).unwrap());
+        assert_eq!(4, *construct.current_storage().get(&Product::ware("Ores")).unwrap());
+        assert_eq!(None, construct.current_storage().get(&Product::ware("Metals")));
 
         ore_production.next_turn(&6, &mut construct);
         metal_production.next_turn(&6, &mut construct);
@@ -216,8 +599,48 @@ mod tests_int {
         assert_eq!(7, ore_production.production_trigger_time);
         assert_eq!(9, metal_production.production_trigger_time);
 
-        assert_eq!(190, *construct.current_storage().get(&Product::PowerCells).unwrap());
-        assert_eq!(2, *construct.current_storage().get(&Product::Ores).unwrap());
-        assert_eq!(1, *construct.current_storage().get(&Product::Metals).unwrap());
+        assert_eq!(190, *construct.current_storage().get(&Product::ware("PowerCells")).unwrap());
+        assert_eq!(2, *construct.current_storage().get(&Product::ware("Ores")).unwrap());
+        assert_eq!(1, *construct.current_storage().get(&Product::ware("Metals")).unwrap());
+    }
+
+    #[test]
+    fn orders_give_back_the_own_recipe() {
+        let power_to_ore = Recipe::new("PowerToOre".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 2)], 1);
+        let ore_to_metal = Recipe::new("OreToMetal".to_string(), vec![Amount::new(Product::ware("Ores"), 2)], vec![Amount::new(Product::ware("Metals"), 1)], 2);
+        let mut production_module = ProductionModule::from_recipe("Factory".to_string(), &power_to_ore, 0);
+        let storage = HashMap::new();
+
+        // Removing the last order goes back to the own recipe, once the batch is done.
+        production_module.add_order(ProductionOrder::new(ore_to_metal.clone(), OrderTarget::Batches(5)));
+        production_module.advance_orders(&storage);
+        assert_eq!(&Some("OreToMetal".to_string()), production_module.recipe_id());
+        production_module.set_stored_input(true);
+        assert!(production_module.remove_order(0).is_ok());
+        assert_eq!(&Some("OreToMetal".to_string()), production_module.recipe_id());
+        production_module.set_stored_input(false);
+        production_module.advance_orders(&storage);
+        assert_eq!(&Some("PowerToOre".to_string()), production_module.recipe_id());
+        assert_eq!(1, production_module.production_time());
+        assert!(production_module.enabled());
+
+        production_module.add_order(ProductionOrder::new(ore_to_metal.clone(), OrderTarget::Batches(5)));
+        production_module.advance_orders(&storage);
+        assert!(production_module.remove_order(0).is_ok());
+        assert_eq!(&Some("PowerToOre".to_string()), production_module.recipe_id());
+        assert_eq!(&vec![Amount::new(Product::ware("Ores"), 2)], production_module.output());
+
+        // Finishing the last order goes back to the own recipe and stops the module.
+        production_module.add_order(ProductionOrder::new(ore_to_metal, OrderTarget::Batches(1)));
+        production_module.advance_orders(&storage);
+        production_module.record_output(&vec![Amount::new(Product::ware("Metals"), 1)]);
+        production_module.advance_orders(&storage);
+        assert!(production_module.orders().is_empty());
+        assert_eq!(&Some("PowerToOre".to_string()), production_module.recipe_id());
+        assert!(!production_module.enabled());
+
+        // Orders do not enable a stopped module.
+        production_module.add_order(ProductionOrder::new(power_to_ore, OrderTarget::Batches(1)));
+        assert!(!production_module.enabled());
     }
 }
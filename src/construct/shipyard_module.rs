@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::blueprints::Blueprint;
+use crate::construct::amount::Amount;
+use crate::construct::construct::unload;
+use crate::construct::construct_position::ConstructPositionEventReturnType;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::Product;
+use crate::wear::ModuleWear;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct ShipyardOrder {
+    construct_name: String,
+    blueprint: Blueprint,
+    // The work done, each turn adds the efficiency percent of the shipyard until it reaches build_time * 100.
+    #[serde(default)]
+    progress: u32,
+    finished_turn: Option<u64>,
+}
+
+impl ShipyardOrder {
+    pub fn construct_name(&self) -> &str {
+        &self.construct_name
+    }
+    pub fn blueprint(&self) -> &Blueprint {
+        &self.blueprint
+    }
+    pub fn progress(&self) -> u32 {
+        self.progress
+    }
+    // None while the shipyard is still building.
+    pub fn finished_turn(&self) -> Option<u64> {
+        self.finished_turn
+    }
+
+    fn total_work(&self) -> u32 {
+        (self.blueprint.build_time() * 100).max(1)
+    }
+
+    // The share of the cost that is taken once that much work is done, rounded up so the last turn takes the rest.
+    fn materials_for(&self, progress: u32) -> Vec<Amount> {
+        let total_work = u64::from(self.total_work());
+        self.blueprint.cost().iter()
+            .map(|amount| {
+                let share = (u64::from(amount.amount()) * u64::from(progress)).div_ceil(total_work);
+                Amount::new(amount.product().clone(), share as u32)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct ShipyardModule {
+    name: String,
+    order: Option<ShipyardOrder>,
+    #[serde(default)]
+    wear: ModuleWear,
+}
+
+impl ShipyardModule {
+    pub fn new(name: String) -> Self {
+        ShipyardModule { name, order: None, wear: ModuleWear::default() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn order(&self) -> &Option<ShipyardOrder> {
+        &self.order
+    }
+    pub fn wear(&self) -> &ModuleWear {
+        &self.wear
+    }
+    pub fn wear_mut(&mut self) -> &mut ModuleWear {
+        &mut self.wear
+    }
+
+    pub fn start_order(&mut self, construct_name: String, blueprint: Blueprint) -> Result<(), String> {
+        if let Some(order) = &self.order {
+            return Err(format!("Shipyard {} is already building {}.", self.name, order.construct_name));
+        }
+        self.order = Some(ShipyardOrder { construct_name, blueprint, progress: 0, finished_turn: None });
+        Ok(())
+    }
+
+    // Takes the materials bit by bit over the build time, a turn without the materials for its share of the work is lost.
+    // A worn shipyard does less work per turn, a broken down one none. The construct is launched the turn after the work is done.
+    pub fn handle_turn(&mut self, current_storage: &mut HashMap<Product, u32>, current_turn: &u64) {
+        if self.wear.is_broken_down() {
+            return;
+        }
+        let efficiency_percent = self.wear.efficiency_percent();
+        if let Some(order) = &mut self.order {
+            if order.finished_turn.is_none() {
+                let progress = (order.progress + efficiency_percent).min(order.total_work());
+                let taken = order.materials_for(order.progress);
+                let needed = order.materials_for(progress).into_iter()
+                    .zip(taken)
+                    .map(|(due, taken)| Amount::new(due.product().clone(), due.amount() - taken.amount()))
+                    .filter(|amount| amount.amount() > 0)
+                    .collect::<Vec<Amount>>();
+                let all_materials_stored = needed.iter()
+                    .all(|amount| current_storage.get(amount.product()).unwrap_or(&0) >= &amount.amount());
+                if all_materials_stored {
+                    for amount in &needed {
+                        unload(current_storage, amount);
+                    }
+                    order.progress = progress;
+                    if order.progress == order.total_work() {
+                        order.finished_turn = Some(current_turn + 1);
+                        self.wear.record_cycle();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn take_finished_order(&mut self, current_turn: &u64) -> Option<ShipyardOrder> {
+        match &self.order {
+            Some(ShipyardOrder { finished_turn: Some(finished_turn), .. }) if finished_turn <= current_turn => self.order.take(),
+            _ => None
+        }
+    }
+}
+
+impl MyLittleUniverse {
+    pub(crate) fn is_construct_name_taken(&self, construct_name: &str) -> bool {
+        self.constructs.contains_key(construct_name)
+            || self.constructs.values()
+            .flat_map(|construct| construct.shipyard_orders())
+            .any(|order| order.construct_name.eq(construct_name))
+    }
+
+    // The new construct docks at its shipyard when there is a free slot, otherwise it is placed next to the shipyard.
+    // A shipyard in transit launches its finished constructs after it arrived.
+    pub(crate) fn launch_finished_constructs(&mut self, current_turn: &u64) {
+        for shipyard_name in self.construct_names_in_turn_order() {
+            let sector_position = match self.get_sector_position(shipyard_name.clone()) {
+                Some(sector_position) => sector_position.clone(),
+                None => continue
+            };
+            let finished_orders = self.constructs.get_mut(&shipyard_name).unwrap().take_finished_orders(current_turn);
+            for order in finished_orders {
+                let mut construct = order.blueprint.build(order.construct_name.clone(), sector_position.clone());
+
+                match self.constructs.get_mut(&shipyard_name).unwrap().handle_docking_request(order.construct_name.clone()) {
+                    ConstructPositionEventReturnType::RequestProcessed => {
+                        construct.handle_docked(shipyard_name.clone());
+                    }
+                    _ => {
+                        if let Some(sector) = self.sectors.get_mut(sector_position.sector_position()) {
+                            sector.enter_sector(order.construct_name.clone(), Some(sector_position.group_address()));
+                        }
+                    }
+                }
+                self.constructs.insert(order.construct_name, construct);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::blueprints::read_blueprint_catalog;
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::construct_position::ConstructPositionStatus::{InSector, IsDocked};
+    use crate::construct::shipyard_module::ShipyardModule;
+    use crate::construct_module::ConstructModuleType::Shipyard;
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::sector::{ExternalSectorEventType, SectorEvenReturnType, SectorPosition};
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn shipyard_builds_constructs() {
+        let recipe_catalog = read_recipe_catalog("default");
+        let mut universe = generate_simple_universe("shipyard".to_string(), &recipe_catalog);
+        universe.set_blueprints(read_blueprint_catalog("default", &recipe_catalog));
+        let shipyard = universe.constructs.get_mut("The_base_1").unwrap();
+        assert_eq!(Ok(()), shipyard.install(Shipyard(ShipyardModule::new("Yard".to_string()))));
+        // Keeps the power cells for the shipyard.
+        shipyard.push_event(&ConstructEventType::External(ExternalConstructEventType::SetModuleEnabled { module_name: "PowerToOre".to_string(), enabled: false }));
+
+        let build = |blueprint_id: &str, construct_name: &str| ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::BuildConstruct {
+            module_name: "Yard".to_string(),
+            blueprint_id: blueprint_id.to_string(),
+            construct_name: construct_name.to_string(),
+        });
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("No blueprint named Unknown.".to_string())), universe.handle_event(build("Unknown", "hauler")));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("A construct named transport already exists.".to_string())), universe.handle_event(build("Transport", "transport")));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::BuildOrdered), universe.handle_event(build("Transport", "hauler")));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("Shipyard Yard is already building hauler.".to_string())), universe.handle_event(build("Transport", "hauler_2")));
+
+        // Waiting for materials.
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        assert!(universe.request_execute_turn());
+        let shipyard = universe.constructs.get_mut("The_base_1").unwrap();
+        shipyard.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 50))));
+        shipyard.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 20))));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("Shipyard Yard is still building.".to_string())),
+                   universe.handle_event(ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::UninstallModule { module_name: "Yard".to_string() })));
+
+        // A tenth of the materials is taken on each of the 10 turns of work from turn 2, so the construct is launched on turn 12.
+        for turn in 2..12 {
+            assert!(universe.request_execute_turn());
+            assert!(!universe.constructs().contains_key("hauler"));
+            let storage = universe.constructs().get("The_base_1").unwrap().current_storage();
+            assert_eq!(50 - 5 * (turn - 1), storage.get(&Product::ware("Metals")).copied().unwrap_or(0));
+            assert_eq!(20 - 2 * (turn - 1), storage.get(&Product::ware("PowerCells")).copied().unwrap_or(0));
+        }
+        assert!(universe.request_execute_turn());
+        let hauler = universe.constructs().get("hauler").unwrap();
+        assert_eq!(500, hauler.capacity());
+        assert_eq!(IsDocked("The_base_1".to_string()), *hauler.position().position());
+
+        // The docking slot is taken, so the next construct is placed in the sector group of the shipyard.
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::BuildOrdered), universe.handle_event(build("OreMine", "mine")));
+        let shipyard = universe.constructs.get_mut("The_base_1").unwrap();
+        shipyard.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 100))));
+        // Without the power cells for its share of the work, the turn is lost.
+        assert!(universe.request_execute_turn());
+        let shipyard = universe.constructs.get_mut("The_base_1").unwrap();
+        assert_eq!(Some(0), shipyard.shipyard_orders().first().map(|order| order.progress()));
+        assert_eq!(Some(&100), shipyard.current_storage().get(&Product::ware("Metals")));
+        shipyard.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 50))));
+        for _ in 0..21 {
+            assert!(universe.request_execute_turn());
+        }
+        let mine = universe.constructs().get("mine").unwrap();
+        assert!(matches!(mine.position().position(), InSector(position) if position.sector_position().eq(&SectorPosition::new(1, 1, 1))));
+        assert!(mine.production_module("PowerToOre").is_some());
+        if let ExternalCommandReturnValues::Sector(SectorEvenReturnType::SectorState(sector)) = universe.handle_event(ExternalCommands::Sector(SectorPosition::new(1, 1, 1), ExternalSectorEventType::GetSectorState)) {
+            assert!(format!("{:?}", sector).contains("mine"));
+        } else {
+            panic!("Expected the sector state");
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::cmp::min;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::amount::Amount;
+use crate::construct_module::ConstructModuleType;
+use crate::products::{Product, ProductCategory};
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct StorageModule {
+    name: String,
+    hold: ProductCategory,
+    capacity: u32,
+}
+
+impl StorageModule {
+    pub fn new(name: String, hold: ProductCategory, capacity: u32) -> Self {
+        StorageModule { name, hold, capacity }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn hold(&self) -> ProductCategory {
+        self.hold
+    }
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum Hold {
+    General,
+    Typed(ProductCategory),
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct HoldFillLevel {
+    hold: Hold,
+    used_volume: u32,
+    capacity: u32,
+}
+
+impl HoldFillLevel {
+    pub fn hold(&self) -> Hold {
+        self.hold
+    }
+    pub fn used_volume(&self) -> u32 {
+        self.used_volume
+    }
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+}
+
+// Products fill the typed hold of their category first and overflow into the general hold, that is
+// the construct capacity. So storage fits as long as the overflow of all categories fits in the general hold.
+pub struct StorageCapacity {
+    general: u32,
+    typed: HashMap<ProductCategory, u32>,
+}
+
+impl StorageCapacity {
+    pub fn new(general: u32, modules: &Vec<ConstructModuleType>) -> Self {
+        let mut typed = HashMap::new();
+        for module in modules {
+            if let ConstructModuleType::Storage(storage_module) = module {
+                *typed.entry(storage_module.hold).or_insert(0) += storage_module.capacity;
+            }
+        }
+        StorageCapacity { general, typed }
+    }
+
+    pub fn free_volume_for(&self, current_storage: &HashMap<Product, u32>, category: ProductCategory) -> u32 {
+        let used_per_category = used_volume_per_category(current_storage);
+        let used = *used_per_category.get(&category).unwrap_or(&0);
+        self.typed_capacity(category).saturating_sub(used)
+            + self.general.saturating_sub(self.general_used_volume(&used_per_category))
+    }
+
+    pub fn fits(&self, current_storage: &HashMap<Product, u32>, additional: &Vec<Amount>) -> bool {
+        let mut used_per_category = used_volume_per_category(current_storage);
+        for amount in additional {
+            *used_per_category.entry(amount.product().category()).or_insert(0) += amount.product().volume() * amount.amount();
+        }
+        self.general_used_volume(&used_per_category) <= self.general
+    }
+
+    pub fn fill_levels(&self, current_storage: &HashMap<Product, u32>) -> Vec<HoldFillLevel> {
+        let used_per_category = used_volume_per_category(current_storage);
+        let mut fill_levels = vec![HoldFillLevel {
+            hold: Hold::General,
+            used_volume: self.general_used_volume(&used_per_category),
+            capacity: self.general,
+        }];
+        for category in [ProductCategory::Bulk, ProductCategory::Energy, ProductCategory::Modules] {
+            let capacity = self.typed_capacity(category);
+            if capacity > 0 {
+                fill_levels.push(HoldFillLevel {
+                    hold: Hold::Typed(category),
+                    used_volume: min(*used_per_category.get(&category).unwrap_or(&0), capacity),
+                    capacity,
+                });
+            }
+        }
+        fill_levels
+    }
+
+    fn typed_capacity(&self, category: ProductCategory) -> u32 {
+        *self.typed.get(&category).unwrap_or(&0)
+    }
+
+    fn general_used_volume(&self, used_per_category: &HashMap<ProductCategory, u32>) -> u32 {
+        used_per_category.iter()
+            .map(|(category, used)| used.saturating_sub(self.typed_capacity(*category)))
+            .sum()
+    }
+}
+
+fn used_volume_per_category(current_storage: &HashMap<Product, u32>) -> HashMap<ProductCategory, u32> {
+    let mut used_per_category = HashMap::new();
+    for (product, amount) in current_storage {
+        *used_per_category.entry(product.category()).or_insert(0) += product.volume() * amount;
+    }
+    used_per_category
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{Construct, ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::construct_position::ConstructPositionSector;
+    use crate::construct::storage_module::{Hold, HoldFillLevel, StorageModule};
+    use crate::construct_module::ConstructModuleType::Storage;
+    use crate::products::{Product, ProductCategory};
+    use crate::sector::SectorPosition;
+
+    #[test]
+    fn typed_holds_only_take_their_category() {
+        let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
+        let mut construct = Construct::new("The base".to_string(), 10, sector_position);
+        assert_eq!(Ok(()), construct.install(Storage(StorageModule::new("Batteries".to_string(), ProductCategory::Energy, 20))));
+
+        assert_eq!(ConstructEvenReturnType::RequestLoadProcessed(25), construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 25)))));
+        assert_eq!(ConstructEvenReturnType::RequestLoadProcessed(5), construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Ores"), 20)))));
+        assert!(!construct.has_room_for(&vec![Amount::new(Product::ware("PowerCells"), 1)]));
+
+        assert_eq!(vec![
+            HoldFillLevel { hold: Hold::General, used_volume: 10, capacity: 10 },
+            HoldFillLevel { hold: Hold::Typed(ProductCategory::Energy), used_volume: 20, capacity: 20 },
+        ], construct.storage_capacity().fill_levels(construct.current_storage()));
+
+        // Unloading the ores frees the general hold for power cells again.
+        assert_eq!(ConstructEvenReturnType::RequestUnloadProcessed(5), construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(Amount::new(Product::ware("Ores"), 5)))));
+        assert!(construct.has_room_for(&vec![Amount::new(Product::ware("PowerCells"), 5)]));
+        assert!(!construct.has_room_for(&vec![Amount::new(Product::ware("PowerCells"), 6)]));
+
+        if let ConstructEvenReturnType::ConstructState(state) = construct.push_event(&ConstructEventType::External(ExternalConstructEventType::GetConstructState { include_stack: false })) {
+            assert_eq!(vec![
+                HoldFillLevel { hold: Hold::General, used_volume: 5, capacity: 10 },
+                HoldFillLevel { hold: Hold::Typed(ProductCategory::Energy), used_volume: 20, capacity: 20 },
+            ], *state.hold_fill_levels());
+        } else {
+            panic!("Expected the construct state");
+        }
+    }
+}
@@ -1,22 +1,75 @@
 use serde::{Deserialize, Serialize};
 
+use crate::construct::generator_module::GeneratorModule;
+use crate::construct::mining_module::MiningModule;
 use crate::construct::production_module::ProductionModule;
+use crate::construct::shipyard_module::ShipyardModule;
+use crate::construct::storage_module::StorageModule;
+use crate::wear::{ModuleWear, WearModuleType};
 
+// Production phase hook, see TurnPhase::Production.
 pub trait CanHandleNextTurn {
     fn next_turn(&mut self, current_turn: &u64);
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
 pub enum ConstructModuleType {
-    Production(ProductionModule)
+    Production(ProductionModule),
+    Storage(StorageModule),
+    Shipyard(ShipyardModule),
+    Generator(GeneratorModule),
+    Mining(MiningModule),
 }
 
 impl ConstructModuleType {
+    // None for modules that do not wear.
+    pub fn wear_mut(&mut self) -> Option<(WearModuleType, &mut ModuleWear)> {
+        match self {
+            ConstructModuleType::Production(production_module) => Some((WearModuleType::Production, production_module.wear_mut())),
+            ConstructModuleType::Generator(generator_module) => Some((WearModuleType::Generator, generator_module.wear_mut())),
+            ConstructModuleType::Shipyard(shipyard_module) => Some((WearModuleType::Shipyard, shipyard_module.wear_mut())),
+            ConstructModuleType::Mining(mining_module) => Some((WearModuleType::Mining, mining_module.wear_mut())),
+            ConstructModuleType::Storage(_) => None,
+        }
+    }
+
+    pub fn wear(&self) -> Option<&ModuleWear> {
+        match self {
+            ConstructModuleType::Production(production_module) => Some(production_module.wear()),
+            ConstructModuleType::Generator(generator_module) => Some(generator_module.wear()),
+            ConstructModuleType::Shipyard(shipyard_module) => Some(shipyard_module.wear()),
+            ConstructModuleType::Mining(mining_module) => Some(mining_module.wear()),
+            ConstructModuleType::Storage(_) => None,
+        }
+    }
+
+    pub(crate) fn set_name(&mut self, name: String) {
+        match self {
+            ConstructModuleType::Production(production_module) => production_module.set_name(name),
+            ConstructModuleType::Storage(storage_module) => storage_module.set_name(name),
+            ConstructModuleType::Shipyard(shipyard_module) => shipyard_module.set_name(name),
+            ConstructModuleType::Generator(generator_module) => generator_module.set_name(name),
+            ConstructModuleType::Mining(mining_module) => mining_module.set_name(name),
+        }
+    }
+
     pub fn name(&self) -> &str {
         return match self {
             ConstructModuleType::Production(production_module) => {
                 production_module.name()
             }
+            ConstructModuleType::Storage(storage_module) => {
+                storage_module.name()
+            }
+            ConstructModuleType::Shipyard(shipyard_module) => {
+                shipyard_module.name()
+            }
+            ConstructModuleType::Generator(generator_module) => {
+                generator_module.name()
+            }
+            ConstructModuleType::Mining(mining_module) => {
+                mining_module.name()
+            }
         };
     }
 }
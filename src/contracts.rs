@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+
+use crate::credits::{TransactionKind, WalletId};
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::Product;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq)]
+pub enum ContractStatus {
+    Open,
+    Accepted,
+    Fulfilled,
+    Expired,
+}
+
+// Deliver the quantity of the product to the issuer by the deadline turn, the reward is held by the contract until then.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Contract {
+    id: u64,
+    issuer: String,
+    product: Product,
+    quantity: u32,
+    delivered: u32,
+    deadline_turn: u64,
+    reward: u64,
+    penalty: u64,
+    contractor: Option<String>,
+    status: ContractStatus,
+}
+
+impl Contract {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+    pub fn product(&self) -> &Product {
+        &self.product
+    }
+    pub fn quantity(&self) -> u32 {
+        self.quantity
+    }
+    pub fn delivered(&self) -> u32 {
+        self.delivered
+    }
+    pub fn deadline_turn(&self) -> u64 {
+        self.deadline_turn
+    }
+    pub fn reward(&self) -> u64 {
+        self.reward
+    }
+    pub fn penalty(&self) -> u64 {
+        self.penalty
+    }
+    pub fn contractor(&self) -> &Option<String> {
+        &self.contractor
+    }
+    pub fn status(&self) -> ContractStatus {
+        self.status
+    }
+    fn is_running(&self) -> bool {
+        self.status == ContractStatus::Open || self.status == ContractStatus::Accepted
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct OfPostContract {
+    issuer: String,
+    product: Product,
+    quantity: u32,
+    deadline_turn: u64,
+    reward: u64,
+    penalty: u64,
+}
+
+impl OfPostContract {
+    pub fn new(issuer: String, product: Product, quantity: u32, deadline_turn: u64, reward: u64, penalty: u64) -> Self {
+        OfPostContract { issuer, product, quantity, deadline_turn, reward, penalty }
+    }
+
+    pub(crate) fn product_mut(&mut self) -> &mut Product {
+        &mut self.product
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub struct ContractBoard {
+    contracts: Vec<Contract>,
+    next_contract_id: u64,
+}
+
+impl ContractBoard {
+    pub fn contracts(&self) -> &Vec<Contract> {
+        &self.contracts
+    }
+    pub fn contract(&self, contract_id: u64) -> Option<&Contract> {
+        self.contracts.iter().find(|contract| contract.id == contract_id)
+    }
+}
+
+impl MyLittleUniverse {
+    pub fn contract_board(&self) -> &ContractBoard {
+        &self.contract_board
+    }
+
+    pub(crate) fn post_contract(&mut self, of_post_contract: OfPostContract) -> Result<u64, String> {
+        if !self.constructs.contains_key(&of_post_contract.issuer) {
+            return Err(format!("No construct named {}.", of_post_contract.issuer));
+        }
+        if of_post_contract.quantity == 0 {
+            return Err("A contract needs a quantity above zero.".to_string());
+        }
+        if of_post_contract.deadline_turn < self.time().turn() {
+            return Err(format!("The deadline turn {} has already passed.", of_post_contract.deadline_turn));
+        }
+
+        let id = self.contract_board.next_contract_id;
+        let issuer_wallet = self.wallet_of(&of_post_contract.issuer);
+        self.credits.transfer(self.time().turn(), &issuer_wallet, &WalletId::Contract(id), of_post_contract.reward, TransactionKind::Contract)?;
+        self.contract_board.next_contract_id += 1;
+        self.contract_board.contracts.push(Contract {
+            id,
+            issuer: of_post_contract.issuer,
+            product: of_post_contract.product,
+            quantity: of_post_contract.quantity,
+            delivered: 0,
+            deadline_turn: of_post_contract.deadline_turn,
+            reward: of_post_contract.reward,
+            penalty: of_post_contract.penalty,
+            contractor: None,
+            status: ContractStatus::Open,
+        });
+        Ok(id)
+    }
+
+    pub(crate) fn accept_contract(&mut self, contract_id: u64, contractor: String) -> Result<(), String> {
+        if !self.constructs.contains_key(&contractor) {
+            return Err(format!("No construct named {}.", contractor));
+        }
+        let contract = match self.contract_board.contracts.iter_mut().find(|contract| contract.id == contract_id) {
+            Some(contract) => contract,
+            None => return Err(format!("No contract with id {}.", contract_id))
+        };
+        if contract.status != ContractStatus::Open {
+            return Err(format!("Contract {} is {:?} and cannot be accepted.", contract_id, contract.status));
+        }
+        if contract.issuer.eq(&contractor) {
+            return Err(format!("{} cannot accept its own contract.", contractor));
+        }
+        contract.contractor = Some(contractor);
+        contract.status = ContractStatus::Accepted;
+        Ok(())
+    }
+
+    // Called for every cargo transfer, the cargo counts for the oldest accepted contracts first.
+    pub(crate) fn deliver_to_contracts(&mut self, contractor: &str, receiver: &str, product: &Product, amount: u32) {
+        let current_turn = self.time().turn();
+        let mut remaining = amount;
+        let mut fulfilled = Vec::new();
+        for contract in self.contract_board.contracts.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            if contract.status != ContractStatus::Accepted || contract.issuer.ne(receiver) || contract.contractor.as_deref() != Some(contractor) || contract.product.ne(product) {
+                continue;
+            }
+            let delivered = std::cmp::min(remaining, contract.quantity - contract.delivered);
+            contract.delivered += delivered;
+            remaining -= delivered;
+            if contract.delivered == contract.quantity {
+                contract.status = ContractStatus::Fulfilled;
+                fulfilled.push((contract.id, contract.reward));
+            }
+        }
+
+        let contractor_wallet = self.wallet_of(contractor);
+        for (contract_id, reward) in fulfilled {
+            self.credits.transfer(current_turn, &WalletId::Contract(contract_id), &contractor_wallet, reward, TransactionKind::Contract)
+                .expect("The reward is held by the contract.");
+        }
+    }
+
+    // Expired contracts return their reward to the issuer, a contractor that accepted it pays the penalty as far as it can.
+    pub(crate) fn evaluate_contracts(&mut self, current_turn: &u64) {
+        let expired: Vec<Contract> = self.contract_board.contracts.iter_mut()
+            .filter(|contract| contract.is_running() && contract.deadline_turn < *current_turn)
+            .map(|contract| {
+                contract.status = ContractStatus::Expired;
+                contract.clone()
+            })
+            .collect();
+
+        for contract in expired {
+            let issuer_wallet = self.wallet_of(&contract.issuer);
+            self.credits.transfer(*current_turn, &WalletId::Contract(contract.id), &issuer_wallet, contract.reward, TransactionKind::Contract)
+                .expect("The reward is held by the contract.");
+            if let Some(contractor) = &contract.contractor {
+                let contractor_wallet = self.wallet_of(contractor);
+                let penalty = std::cmp::min(contract.penalty, self.credits.balance(&contractor_wallet));
+                self.credits.transfer(*current_turn, &contractor_wallet, &issuer_wallet, penalty, TransactionKind::Penalty)
+                    .expect("The penalty is limited to the balance.");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::construct_position::ExternalConstructPositionEventType;
+    use crate::contracts::{ContractStatus, OfPostContract};
+    use crate::credits::WalletId;
+    use crate::my_little_universe::{ExternalUniverseEventType, MyLittleUniverseReturnValues, OfMove, OfTransferCargo};
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::{add_distant_sector, generate_simple_universe};
+
+    #[test]
+    fn contracts_are_fulfilled_by_deliveries_or_expire() {
+        let mut universe = generate_simple_universe("contracts".to_string(), &read_recipe_catalog("default"));
+        let base = WalletId::Construct("The_base_1".to_string());
+        let transport = WalletId::Construct("transport".to_string());
+        universe.credits.grant(0, &base, 100);
+        universe.credits.grant(0, &transport, 5);
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 30))));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+
+        let post = |universe: &mut crate::my_little_universe::MyLittleUniverse, reward: u64| {
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::PostContract(OfPostContract::new("The_base_1".to_string(), Product::ware("Metals"), 20, 2, reward, 10))))
+        };
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Construct(\"The_base_1\") has 100 credits and cannot pay 150.".to_string())),
+            post(&mut universe, 150)
+        );
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ContractPosted(0)), post(&mut universe, 50));
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ContractPosted(1)), post(&mut universe, 30));
+        assert_eq!(20, universe.credits().balance(&base));
+
+        let accept = |universe: &mut crate::my_little_universe::MyLittleUniverse, contract_id: u64| {
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::AcceptContract { contract_id, contractor: "transport".to_string() }))
+        };
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ContractAccepted), accept(&mut universe, 0));
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ContractAccepted), accept(&mut universe, 1));
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Contract 0 is Accepted and cannot be accepted.".to_string())),
+            accept(&mut universe, 0)
+        );
+
+        // The base takes 25 metals from the transport, enough for the first contract only.
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
+            OfTransferCargo::new("The_base_1".to_string(), "transport".to_string(), Amount::new(Product::ware("Metals"), 25))
+        )));
+        assert_eq!(ContractStatus::Fulfilled, universe.contract_board().contract(0).unwrap().status());
+        assert_eq!(5, universe.contract_board().contract(1).unwrap().delivered());
+        assert_eq!(55, universe.credits().balance(&transport));
+
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        for _ in 0..3 {
+            assert!(universe.request_execute_turn());
+        }
+        assert_eq!(ContractStatus::Expired, universe.contract_board().contract(1).unwrap().status());
+        assert_eq!(60, universe.credits().balance(&base));
+        assert_eq!(45, universe.credits().balance(&transport));
+        assert_eq!(0, universe.credits().balance(&WalletId::Contract(1)));
+    }
+
+    #[test]
+    fn nothing_is_delivered_in_transit() {
+        let mut universe = generate_simple_universe("contracts".to_string(), &read_recipe_catalog("default"));
+        let distant = add_distant_sector(&mut universe);
+        universe.credits.grant(0, &WalletId::Construct("The_base_1".to_string()), 100);
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 20))));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::PostContract(OfPostContract::new("The_base_1".to_string(), Product::ware("Metals"), 20, 2, 50, 10))));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::AcceptContract { contract_id: 0, contractor: "transport".to_string() }));
+
+        // The transport travels docked at the base, the deadline is over before they arrive.
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("The_base_1".to_string(), distant, Some(0)))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        for _ in 0..3 {
+            assert_eq!(
+                ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Cannot transfer, because The_base_1 is in transit.".to_string())),
+                universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
+                    OfTransferCargo::new("The_base_1".to_string(), "transport".to_string(), Amount::new(Product::ware("Metals"), 20))
+                ))),
+            );
+            assert!(universe.request_execute_turn());
+        }
+        assert_eq!(0, universe.contract_board().contract(0).unwrap().delivered());
+        assert_eq!(ContractStatus::Expired, universe.contract_board().contract(0).unwrap().status());
+    }
+
+    #[test]
+    fn cargo_that_does_not_fit_is_not_delivered() {
+        let mut universe = generate_simple_universe("contracts".to_string(), &read_recipe_catalog("default"));
+        universe.credits.grant(0, &WalletId::Construct("The_base_1".to_string()), 100);
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 20))));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::PostContract(OfPostContract::new("The_base_1".to_string(), Product::ware("Metals"), 20, 2, 50, 10))));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::AcceptContract { contract_id: 0, contractor: "transport".to_string() }));
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 1000))));
+
+        // The issuer is full, the metals stay on the transport and the reward stays in escrow.
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CargoTransfered(0)),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
+                OfTransferCargo::new("The_base_1".to_string(), "transport".to_string(), Amount::new(Product::ware("Metals"), 20))
+            ))),
+        );
+        assert_eq!(0, universe.contract_board().contract(0).unwrap().delivered());
+        assert_eq!(ContractStatus::Accepted, universe.contract_board().contract(0).unwrap().status());
+        assert_eq!(Some(&20), universe.constructs().get("transport").unwrap().current_storage().get(&Product::ware("Metals")));
+        assert_eq!(0, universe.credits().balance(&WalletId::Construct("transport".to_string())));
+        assert_eq!(50, universe.credits().balance(&WalletId::Contract(0)));
+    }
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use crate::my_little_universe::MyLittleUniverse;
+
+// Constructs with an owner pay from and get paid into the wallet of their owner.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum WalletId {
+    Owner(String),
+    Construct(String),
+    // Holds the reward of a contract until it is fulfilled or expired.
+    Contract(u64),
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Payment {
+    payee: WalletId,
+    amount: u64,
+}
+
+impl Payment {
+    pub fn new(payee: WalletId, amount: u64) -> Self {
+        Payment { payee, amount }
+    }
+
+    pub fn payee(&self) -> &WalletId {
+        &self.payee
+    }
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq)]
+pub enum TransactionKind {
+    Grant,
+    Transfer,
+    Cargo,
+    // A trade of the market orders.
+    Trade,
+    ModuleInstall,
+    Contract,
+    Penalty,
+}
+
+// A grant has no payer, it is how credits enter the universe.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Transaction {
+    turn: u64,
+    payer: Option<WalletId>,
+    payee: WalletId,
+    amount: u64,
+    kind: TransactionKind,
+}
+
+impl Transaction {
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+    pub fn payer(&self) -> &Option<WalletId> {
+        &self.payer
+    }
+    pub fn payee(&self) -> &WalletId {
+        &self.payee
+    }
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+    pub fn kind(&self) -> TransactionKind {
+        self.kind
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Wallet {
+    id: WalletId,
+    balance: u64,
+}
+
+// A wallet exists from the first time it receives credits.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub struct Credits {
+    wallets: Vec<Wallet>,
+    ledger: Vec<Transaction>,
+}
+
+impl Credits {
+    pub fn wallets(&self) -> &Vec<Wallet> {
+        &self.wallets
+    }
+    pub fn ledger(&self) -> &Vec<Transaction> {
+        &self.ledger
+    }
+
+    pub fn balance(&self, wallet_id: &WalletId) -> u64 {
+        self.wallets.iter()
+            .find(|wallet| wallet.id.eq(wallet_id))
+            .map_or(0, |wallet| wallet.balance)
+    }
+
+    pub fn can_pay(&self, wallet_id: &WalletId, amount: u64) -> bool {
+        self.balance(wallet_id) >= amount
+    }
+
+    pub fn transactions_of(&self, wallet_id: &WalletId) -> Vec<Transaction> {
+        self.ledger.iter()
+            .filter(|transaction| transaction.payee.eq(wallet_id) || transaction.payer.as_ref() == Some(wallet_id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn grant(&mut self, turn: u64, payee: &WalletId, amount: u64) {
+        self.wallet_mut(payee).balance += amount;
+        self.ledger.push(Transaction { turn, payer: None, payee: payee.clone(), amount, kind: TransactionKind::Grant });
+    }
+
+    pub fn transfer(&mut self, turn: u64, payer: &WalletId, payee: &WalletId, amount: u64, kind: TransactionKind) -> Result<(), String> {
+        if !self.can_pay(payer, amount) {
+            return Err(format!("{:?} has {} credits and cannot pay {}.", payer, self.balance(payer), amount));
+        }
+        if amount == 0 || payer.eq(payee) {
+            return Ok(());
+        }
+        self.wallet_mut(payer).balance -= amount;
+        self.wallet_mut(payee).balance += amount;
+        self.ledger.push(Transaction { turn, payer: Some(payer.clone()), payee: payee.clone(), amount, kind });
+        Ok(())
+    }
+
+    fn wallet_mut(&mut self, wallet_id: &WalletId) -> &mut Wallet {
+        match self.wallets.iter().position(|wallet| wallet.id.eq(wallet_id)) {
+            Some(index) => &mut self.wallets[index],
+            None => {
+                self.wallets.push(Wallet { id: wallet_id.clone(), balance: 0 });
+                self.wallets.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+impl MyLittleUniverse {
+    pub fn credits(&self) -> &Credits {
+        &self.credits
+    }
+
+    pub(crate) fn wallet_of(&self, construct_name: &str) -> WalletId {
+        match self.constructs.get(construct_name).and_then(|construct| construct.owner().clone()) {
+            Some(owner) => WalletId::Owner(owner),
+            None => WalletId::Construct(construct_name.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::construct_position::ExternalConstructPositionEventType;
+    use crate::construct::production_module::ProductionModule;
+    use crate::construct_module::ConstructModuleType;
+    use crate::credits::{Payment, TransactionKind, WalletId};
+    use crate::my_little_universe::{ExternalUniverseEventType, MyLittleUniverseReturnValues, OfTransferCargo};
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn credits_pay_for_cargo_and_modules() {
+        let mut universe = generate_simple_universe("credits".to_string(), &read_recipe_catalog("default"));
+        let alice = WalletId::Owner("alice".to_string());
+        let base = WalletId::Construct("The_base_1".to_string());
+
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::OwnerSet),
+            universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::SetOwner(Some("alice".to_string()))))
+        );
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GrantCredits { payee: alice.clone(), amount: 100 }));
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Owner(\"alice\") has 100 credits and cannot pay 150.".to_string())),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCredits { payer: alice.clone(), payee: base.clone(), amount: 150 }))
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CreditsTransferred(10)),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCredits { payer: alice.clone(), payee: base.clone(), amount: 10 }))
+        );
+
+        // The transport buys ores from the base it is docked at, paying only for what it got.
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Ores"), 20))));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+        let buy_ores = |amount: u32| ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
+            OfTransferCargo::new("transport".to_string(), "The_base_1".to_string(), Amount::new(Product::ware("Ores"), amount)).with_price_per_unit(3)
+        ));
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Owner(\"alice\") has 90 credits and cannot pay 150.".to_string())),
+            universe.handle_event(buy_ores(50))
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("A price of {} for 2 units is too high.", u64::MAX))),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
+                OfTransferCargo::new("transport".to_string(), "The_base_1".to_string(), Amount::new(Product::ware("Ores"), 2)).with_price_per_unit(u64::MAX)
+            )))
+        );
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CargoTransfered(20)), universe.handle_event(buy_ores(25)));
+        assert_eq!(30, universe.credits().balance(&alice));
+        assert_eq!(70, universe.credits().balance(&base));
+
+        // Nothing fits into a full transport, so nothing is moved or paid for.
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 1000))));
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Ores"), 20))));
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CargoTransfered(0)), universe.handle_event(buy_ores(10)));
+        assert_eq!(30, universe.credits().balance(&alice));
+        assert_eq!(Some(&20), universe.constructs().get("The_base_1").unwrap().current_storage().get(&Product::ware("Ores")));
+        assert_eq!(Some(&20), universe.constructs().get("transport").unwrap().current_storage().get(&Product::ware("Ores")));
+
+        // The base pays alice for installing a module.
+        let module = ConstructModuleType::Production(ProductionModule::new("Extra".to_string(), vec![], vec![Amount::new(Product::ware("Ores"), 1)], 1, 0));
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::module(module), 1))));
+        let install = |amount: u64| ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::InstallModule { module_name: "Extra".to_string(), installed_name: None, price: Some(Payment::new(alice.clone(), amount)) });
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("Construct(\"The_base_1\") has 70 credits and cannot pay 80.".to_string())),
+            universe.handle_event(install(80))
+        );
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ModuleInstalled), universe.handle_event(install(50)));
+        assert_eq!(80, universe.credits().balance(&alice));
+
+        assert_eq!(
+            vec![TransactionKind::Grant, TransactionKind::Transfer, TransactionKind::Cargo, TransactionKind::ModuleInstall],
+            universe.credits().transactions_of(&alice).iter().map(|transaction| transaction.kind()).collect::<Vec<TransactionKind>>()
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Balance(20)),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GetBalance(base)))
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::mining_module::MiningModule;
+use crate::construct::production_module::ProductionModule;
+use crate::construct_module::ConstructModuleType;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::Product;
+use crate::sector::SectorPosition;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ProductBalance {
+    product: Product,
+    theoretical_production: f64,
+    theoretical_consumption: f64,
+    actual_production: f64,
+    actual_consumption: f64,
+}
+
+impl ProductBalance {
+    pub fn product(&self) -> &Product {
+        &self.product
+    }
+    pub fn theoretical_production(&self) -> f64 {
+        self.theoretical_production
+    }
+    pub fn theoretical_consumption(&self) -> f64 {
+        self.theoretical_consumption
+    }
+    pub fn actual_production(&self) -> f64 {
+        self.actual_production
+    }
+    pub fn actual_consumption(&self) -> f64 {
+        self.actual_consumption
+    }
+
+    // The modules want more of the product than all modules together can make.
+    pub fn is_deficit(&self) -> bool {
+        self.theoretical_consumption > self.theoretical_production
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EconomyBalance {
+    sector_position: Option<SectorPosition>,
+    products: Vec<ProductBalance>,
+    deficits: Vec<Product>,
+}
+
+impl EconomyBalance {
+    pub fn sector_position(&self) -> &Option<SectorPosition> {
+        &self.sector_position
+    }
+    pub fn products(&self) -> &Vec<ProductBalance> {
+        &self.products
+    }
+    pub fn deficits(&self) -> &Vec<Product> {
+        &self.deficits
+    }
+
+    pub fn get(&self, product: &Product) -> Option<&ProductBalance> {
+        self.products.iter().find(|balance| balance.product.eq(product))
+    }
+}
+
+impl Display for EconomyBalance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Economy balance per turn (theoretical / actual):")?;
+        for balance in &self.products {
+            writeln!(f, "  {}: produced {:.2} / {:.2}, consumed {:.2} / {:.2}{}",
+                     balance.product,
+                     balance.theoretical_production,
+                     balance.actual_production,
+                     balance.theoretical_consumption,
+                     balance.actual_consumption,
+                     if balance.is_deficit() { " DEFICIT" } else { "" })?;
+        }
+        Ok(())
+    }
+}
+
+impl MyLittleUniverse {
+    // Theoretical rates assume every enabled module runs without pause, actual rates come from the module statistics.
+    pub fn economy_balance(&self, sector_position: Option<SectorPosition>) -> EconomyBalance {
+        let mut balances: HashMap<Product, ProductBalance> = HashMap::new();
+
+        for (construct_name, construct) in &self.constructs {
+            if let Some(sector_position) = &sector_position {
+                if self.get_sector_position(construct_name.clone()).is_none_or(|position| position.sector_position().ne(sector_position)) {
+                    continue;
+                }
+            }
+            for module in construct.modules() {
+                match module {
+                    ConstructModuleType::Production(production_module) => add_module_balance(&mut balances, production_module),
+                    ConstructModuleType::Mining(mining_module) => add_mining_balance(&mut balances, mining_module),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut products: Vec<ProductBalance> = balances.into_values().collect();
+        products.sort_by_key(|balance| balance.product.to_string());
+        let deficits = products.iter()
+            .filter(|balance| balance.is_deficit())
+            .map(|balance| balance.product.clone())
+            .collect();
+
+        EconomyBalance { sector_position, products, deficits }
+    }
+}
+
+fn add_module_balance(balances: &mut HashMap<Product, ProductBalance>, production_module: &ProductionModule) {
+    let production_time = production_module.leveled_production_time() as f64;
+    let tracked_turns = production_module.stats().tracked_turns().max(1) as f64;
+
+    for amount in production_module.leveled_output() {
+        let balance = product_balance(balances, amount.product());
+        if production_module.enabled() {
+            balance.theoretical_production += amount.amount() as f64 / production_time;
+        }
+    }
+    for amount in production_module.input() {
+        let balance = product_balance(balances, amount.product());
+        if production_module.enabled() {
+            balance.theoretical_consumption += amount.amount() as f64 / production_time;
+        }
+    }
+    for amount in production_module.stats().produced() {
+        product_balance(balances, amount.product()).actual_production += amount.amount() as f64 / tracked_turns;
+    }
+    for amount in production_module.stats().consumed() {
+        product_balance(balances, amount.product()).actual_consumption += amount.amount() as f64 / tracked_turns;
+    }
+}
+
+// Mining only produces, the deposit running out shows in the actual production.
+fn add_mining_balance(balances: &mut HashMap<Product, ProductBalance>, mining_module: &MiningModule) {
+    let tracked_turns = mining_module.tracked_turns().max(1) as f64;
+    let balance = product_balance(balances, &mining_module.product());
+    balance.theoretical_production += mining_module.extraction_per_turn() as f64;
+    balance.actual_production += mining_module.mined() as f64 / tracked_turns;
+}
+
+fn product_balance<'a>(balances: &'a mut HashMap<Product, ProductBalance>, product: &Product) -> &'a mut ProductBalance {
+    balances.entry(product.clone())
+        .or_insert_with(|| ProductBalance {
+            product: product.clone(),
+            theoretical_production: 0.0,
+            theoretical_consumption: 0.0,
+            actual_production: 0.0,
+            actual_consumption: 0.0,
+        })
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEventType, InternalConstructEventType};
+    use crate::my_little_universe::{ExternalUniverseEventType, MyLittleUniverseReturnValues};
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::sector::SectorPosition;
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn balance_of_the_simple_universe() {
+        let mut universe = generate_simple_universe("economy_balance".to_string(), &read_recipe_catalog("default"));
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 100))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        for _ in 0..4 {
+            assert!(universe.request_execute_turn());
+        }
+
+        let balance = universe.economy_balance(None);
+        // PowerToOre makes 2 ores from 1 power cell every turn, OreToPower makes 2 metals from 1 ore every turn.
+        let ores = balance.get(&Product::ware("Ores")).unwrap();
+        assert_eq!(2.0, ores.theoretical_production());
+        assert_eq!(1.0, ores.theoretical_consumption());
+        assert_eq!(1.5, ores.actual_production());
+        assert_eq!(0.0, ores.actual_consumption());
+        assert_eq!(1.0, balance.get(&Product::ware("PowerCells")).unwrap().actual_consumption());
+        assert_eq!(&vec![Product::ware("PowerCells")], balance.deficits());
+
+        let sector_balance = universe.economy_balance(Some(SectorPosition::new(2, 2, 2)));
+        assert_eq!(None, sector_balance.get(&Product::ware("PowerCells")));
+        assert_eq!(&vec![Product::ware("Ores")], sector_balance.deficits());
+
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::EconomyBalance(sector_balance)),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GetEconomyBalance(Some(SectorPosition::new(2, 2, 2)))))
+        );
+    }
+}
@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
 
+use crate::breakpoint::{Breakpoint, BreakpointCondition};
 pub use crate::construct::amount::Amount;
-use crate::construct::construct::{ConstructEvenReturnType, ExternalConstructEventType};
+use crate::construct::construct::{AllocationMode, ConstructEvenReturnType, ExternalConstructEventType};
 use crate::construct::construct_position::ExternalConstructPositionEventType;
+use crate::construct::production_module::OrderTarget;
+use crate::contracts::OfPostContract;
+use crate::credits::{Payment, WalletId};
+use crate::market::OrderSide;
+use crate::pricing::PriceScope;
 use crate::my_little_universe::{ExternalUniverseEventType, MyLittleUniverseReturnValues, OfMove, OfTransferCargo};
-use crate::products::Product;
+use crate::products::{Product, ProductRegistry};
 use crate::save_load::{ExternalSaveLoad, ExternalSaveLoadReturnValue};
 use crate::sector::{ExternalSectorEventType, SectorEvenReturnType, SectorPosition};
 use crate::time::{ExternalTimeEventType, TimeEventReturnType};
@@ -82,10 +88,72 @@ impl ExternalCommands {
                 }
                 return Err(format!("GetTimeStackState optinal booĺ include_stack. Got {:?}", command_parts));
             }
+            "AddBreakpoint" => {
+                if command_parts.len() > 4 {
+                    if let Some(condition) = Self::parse_breakpoint_condition(&command_parts[3..]) {
+                        return Ok(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(command_parts[2].to_string(), condition))));
+                    }
+                }
+                Err(format!("AddBreakpoint need breakpoint_name followed by one of: StorageAtLeast construct_name product amount; StorageAtMost construct_name product amount; OutputStored construct_name module_name; InSector construct_name sector_position. Got {:?}", command_parts))
+            }
+            "RemoveBreakpoint" => {
+                if command_parts.len() > 2 {
+                    return Ok(ExternalCommands::Time(ExternalTimeEventType::RemoveBreakpoint(command_parts[2].to_string())));
+                }
+                Err(format!("RemoveBreakpoint need breakpoint_name. Got {:?}", command_parts))
+            }
             _ => Err(format!("Unknown Time command. Got {:?}", command_parts))
         }
     }
 
+    fn parse_breakpoint_condition(condition_parts: &[&str]) -> Option<BreakpointCondition> {
+        match condition_parts {
+            ["StorageAtLeast", construct_name, product, amount] => {
+                Some(BreakpointCondition::StorageAtLeast { construct_name: construct_name.to_string(), product: Self::parse_product(product)?, amount: amount.parse::<u32>().ok()? })
+            }
+            ["StorageAtMost", construct_name, product, amount] => {
+                Some(BreakpointCondition::StorageAtMost { construct_name: construct_name.to_string(), product: Self::parse_product(product)?, amount: amount.parse::<u32>().ok()? })
+            }
+            ["OutputStored", construct_name, module_name] => {
+                Some(BreakpointCondition::OutputStored { construct_name: construct_name.to_string(), module_name: module_name.to_string() })
+            }
+            ["InSector", construct_name, sector_position] => {
+                Some(BreakpointCondition::InSector { construct_name: construct_name.to_string(), sector_position: Self::parse_sector_position(sector_position)? })
+            }
+            _ => None
+        }
+    }
+
+    // A ware id or Module:<name>
+    fn parse_product(product: &str) -> Option<Product> {
+        match product.strip_prefix("Module:") {
+            Some("") => None,
+            Some(module_name) => Some(Product::module_named(module_name)),
+            None => ProductRegistry::global().product(product)
+        }
+    }
+
+    fn parse_sector_position(sector_position: &str) -> Option<SectorPosition> {
+        let sector_coordinates = sector_position.split("-").collect::<Vec<&str>>();
+        if sector_coordinates.len() != 3 {
+            return None;
+        }
+        Some(SectorPosition::new(
+            sector_coordinates[0].parse::<u8>().ok()?,
+            sector_coordinates[1].parse::<u8>().ok()?,
+            sector_coordinates[2].parse::<u8>().ok()?,
+        ))
+    }
+
+    // Owner alice or Construct the_base_1
+    fn parse_wallet_id(kind: &str, name: &str) -> Option<WalletId> {
+        match kind {
+            "Owner" => Some(WalletId::Owner(name.to_string())),
+            "Construct" => Some(WalletId::Construct(name.to_string())),
+            _ => None
+        }
+    }
+
     fn parse_construct(command_parts: Vec<&str>) -> Result<Self, String> {
         if command_parts.len() < 3 {
             return Err(format!("Construct command needs at least the Construct name and command name. Got {:?}", command_parts));
@@ -104,6 +172,113 @@ impl ExternalCommands {
                 }
                 return Err(format!("GetConstructState optional booĺ include_stack. Got {:?}", command_parts));
             }
+            "SetModulePriority" => {
+                if command_parts.len() == 5 {
+                    if let Ok(priority) = command_parts[4].parse::<u32>() {
+                        return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetModulePriority { module_name: command_parts[3].to_string(), priority }));
+                    }
+                }
+                Err(format!("SetModulePriority needs module_name priority. Got {:?}", command_parts))
+            }
+            "EnableModule" | "DisableModule" => {
+                if command_parts.len() == 4 {
+                    let enabled = command_parts[2].eq("EnableModule");
+                    return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetModuleEnabled { module_name: command_parts[3].to_string(), enabled }));
+                }
+                Err(format!("{} needs module_name. Got {:?}", command_parts[2], command_parts))
+            }
+            "UpgradeModule" => {
+                if command_parts.len() == 4 {
+                    return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::UpgradeModule { module_name: command_parts[3].to_string() }));
+                }
+                Err(format!("UpgradeModule needs module_name. Got {:?}", command_parts))
+            }
+            "AddProductionOrder" => {
+                if command_parts.len() > 5 {
+                    let target = match command_parts[5] {
+                        "Batches" if command_parts.len() == 7 => command_parts[6].parse::<u32>().ok().map(OrderTarget::Batches),
+                        "UntilStock" if command_parts.len() == 8 => match (Self::parse_product(command_parts[6]), command_parts[7].parse::<u32>()) {
+                            (Some(product), Ok(amount)) => Some(OrderTarget::UntilStock(Amount::new(product, amount))),
+                            _ => None
+                        },
+                        _ => None
+                    };
+                    if let Some(target) = target {
+                        return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::AddProductionOrder { module_name: command_parts[3].to_string(), recipe_id: command_parts[4].to_string(), target }));
+                    }
+                }
+                Err(format!("AddProductionOrder needs module_name recipe_id followed by Batches <amount> or UntilStock <product> <amount>. Got {:?}", command_parts))
+            }
+            "RemoveProductionOrder" => {
+                if command_parts.len() == 5 {
+                    if let Ok(index) = command_parts[4].parse::<usize>() {
+                        return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::RemoveProductionOrder { module_name: command_parts[3].to_string(), index }));
+                    }
+                }
+                Err(format!("RemoveProductionOrder needs module_name index. Got {:?}", command_parts))
+            }
+            "BuildConstruct" => {
+                if command_parts.len() == 6 {
+                    return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::BuildConstruct {
+                        module_name: command_parts[3].to_string(),
+                        blueprint_id: command_parts[4].to_string(),
+                        construct_name: command_parts[5].to_string(),
+                    }));
+                }
+                Err(format!("BuildConstruct needs module_name blueprint_id new_construct_name. Got {:?}", command_parts))
+            }
+            "SetAllocationMode" => {
+                if command_parts.len() == 4 {
+                    match command_parts[3] {
+                        "Priority" => return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetAllocationMode(AllocationMode::Priority))),
+                        "FairShare" => return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetAllocationMode(AllocationMode::FairShare))),
+                        _ => {}
+                    }
+                }
+                Err(format!("SetAllocationMode needs Priority or FairShare. Got {:?}", command_parts))
+            }
+            "SetPowerSharing" => {
+                if command_parts.len() == 4 {
+                    if let Ok(share_power) = command_parts[3].parse::<bool>() {
+                        return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetPowerSharing(share_power)));
+                    }
+                }
+                Err(format!("SetPowerSharing needs true or false. Got {:?}", command_parts))
+            }
+            "Buy" | "Sell" => {
+                if command_parts.len() == 6 {
+                    let side = if command_parts[2] == "Buy" { OrderSide::Buy } else { OrderSide::Sell };
+                    if let (Some(product), Ok(quantity), Ok(limit_price)) = (Self::parse_product(command_parts[3]), command_parts[4].parse::<u32>(), command_parts[5].parse::<u32>()) {
+                        return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::PostMarketOrder { side, product, quantity, limit_price }));
+                    }
+                }
+                Err(format!("{} needs product quantity limit_price. Got {:?}", command_parts[2], command_parts))
+            }
+            "CancelMarketOrder" => {
+                if command_parts.len() == 4 {
+                    if let Ok(order_id) = command_parts[3].parse::<u64>() {
+                        return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::CancelMarketOrder { order_id }));
+                    }
+                }
+                Err(format!("CancelMarketOrder needs order_id. Got {:?}", command_parts))
+            }
+            "SetMaintenance" => {
+                if command_parts.len() == 4 {
+                    if let Ok(maintenance) = command_parts[3].parse::<bool>() {
+                        return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetMaintenance(maintenance)));
+                    }
+                }
+                Err(format!("SetMaintenance needs true or false. Got {:?}", command_parts))
+            }
+            "SetOwner" => {
+                if command_parts.len() == 4 {
+                    return Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetOwner(Some(command_parts[3].to_string()))));
+                }
+                Err(format!("SetOwner needs the name of the owner. Got {:?}", command_parts))
+            }
+            "RemoveOwner" => {
+                Ok(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetOwner(None)))
+            }
             _ => Err(format!("Unknown Construct command. Got {:?}", command_parts))
         }
     }
@@ -163,6 +338,20 @@ impl ExternalCommands {
         }
 
         match command_parts[0] {
+            "GetProductionStats" => {
+                Ok(ExternalCommands::Universe(ExternalUniverseEventType::GetProductionStats))
+            }
+            "GetEconomyBalance" => {
+                match command_parts.get(1) {
+                    None => return Ok(ExternalCommands::Universe(ExternalUniverseEventType::GetEconomyBalance(None))),
+                    Some(sector_position) => {
+                        if let Some(sector_position) = Self::parse_sector_position(sector_position) {
+                            return Ok(ExternalCommands::Universe(ExternalUniverseEventType::GetEconomyBalance(Some(sector_position))));
+                        }
+                    }
+                }
+                Err(format!("GetEconomyBalance has an optional sector position like 1-2-3. Got {:?}", command_parts))
+            }
             "Move" => {
                 if command_parts.len() > 2 {
                     // One is construct name
@@ -199,20 +388,18 @@ impl ExternalCommands {
             }
             "TransferCargo" => {
                 if command_parts.len() > 4 {
-                    let product = match command_parts[3] {
-                        "Ores" => { Some(Product::Ores) }
-                        "Metals" => { Some(Product::Metals) }
-                        "PowerCells" => { Some(Product::PowerCells) }
-                        _ => { None }
-                    };
-
-                    if let Some(product_value) = product {
+                    if let Some(product_value) = Self::parse_product(command_parts[3]) {
                         if let Ok(amount) = command_parts[4].parse::<u32>() {
-                            return Ok(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(command_parts[1].to_string(), command_parts[2].to_string(), Amount::new(product_value, amount)))));
+                            let transfer_cargo = OfTransferCargo::new(command_parts[1].to_string(), command_parts[2].to_string(), Amount::new(product_value, amount));
+                            match command_parts.get(5).map(|price| price.parse::<u64>()) {
+                                None => return Ok(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(transfer_cargo))),
+                                Some(Ok(price_per_unit)) => return Ok(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(transfer_cargo.with_price_per_unit(price_per_unit)))),
+                                Some(Err(_)) => {}
+                            }
                         }
                     }
                 }
-                return Err(format!("TransferCargo need source_construct_name target_construct_name product amount. Got {:?}", command_parts));
+                Err(format!("TransferCargo need source_construct_name target_construct_name product amount and optional price_per_unit. Got {:?}", command_parts))
             }
             "Dock" => {
                 if command_parts.len() > 2 {
@@ -220,6 +407,103 @@ impl ExternalCommands {
                 }
                 return Err(format!("Dock need source_construct_name target_construct_name. Got {:?}", command_parts));
             }
+            "InstallModule" | "UninstallModule" => {
+                // InstallModule takes an optional installed_name, followed by an optional price.
+                if command_parts[0].eq("InstallModule") && [3, 4, 6, 7].contains(&command_parts.len()) {
+                    let module_name = command_parts[2].to_string();
+                    let installed_name = [4, 7].contains(&command_parts.len()).then(|| command_parts[3].to_string());
+                    let price = match &command_parts[if installed_name.is_some() { 4 } else { 3 }..] {
+                        [] => Some(None),
+                        [kind, payee_name, amount] => match (Self::parse_wallet_id(kind, payee_name), amount.parse::<u64>()) {
+                            (Some(payee), Ok(amount)) => Some(Some(Payment::new(payee, amount))),
+                            _ => None
+                        },
+                        _ => None
+                    };
+                    if let Some(price) = price {
+                        return Ok(ExternalCommands::Construct(command_parts[1].to_string(), ExternalConstructEventType::InstallModule { module_name, installed_name, price }));
+                    }
+                }
+                if command_parts.len() == 3 && command_parts[0].eq("UninstallModule") {
+                    return Ok(ExternalCommands::Construct(command_parts[1].to_string(), ExternalConstructEventType::UninstallModule { module_name: command_parts[2].to_string() }));
+                }
+                Err(format!("{} need construct_name module_name, InstallModule takes an optional installed_name and an optional Owner|Construct payee_name price. Got {:?}", command_parts[0], command_parts))
+            }
+            "GetBalance" | "GetLedger" => {
+                if command_parts.len() == 3 {
+                    if let Some(wallet_id) = Self::parse_wallet_id(command_parts[1], command_parts[2]) {
+                        let universe_event = if command_parts[0].eq("GetBalance") {
+                            ExternalUniverseEventType::GetBalance(wallet_id)
+                        } else {
+                            ExternalUniverseEventType::GetLedger(wallet_id)
+                        };
+                        return Ok(ExternalCommands::Universe(universe_event));
+                    }
+                }
+                Err(format!("{} need Owner|Construct name. Got {:?}", command_parts[0], command_parts))
+            }
+            "GrantCredits" => {
+                if command_parts.len() == 4 {
+                    if let (Some(payee), Ok(amount)) = (Self::parse_wallet_id(command_parts[1], command_parts[2]), command_parts[3].parse::<u64>()) {
+                        return Ok(ExternalCommands::Universe(ExternalUniverseEventType::GrantCredits { payee, amount }));
+                    }
+                }
+                Err(format!("GrantCredits need Owner|Construct name amount. Got {:?}", command_parts))
+            }
+            "PostContract" => {
+                if command_parts.len() == 7 {
+                    if let (Some(product), Ok(quantity), Ok(deadline_turn), Ok(reward), Ok(penalty)) = (Self::parse_product(command_parts[2]), command_parts[3].parse::<u32>(), command_parts[4].parse::<u64>(), command_parts[5].parse::<u64>(), command_parts[6].parse::<u64>()) {
+                        return Ok(ExternalCommands::Universe(ExternalUniverseEventType::PostContract(OfPostContract::new(command_parts[1].to_string(), product, quantity, deadline_turn, reward, penalty))));
+                    }
+                }
+                Err(format!("PostContract need issuer_name product quantity deadline_turn reward penalty. Got {:?}", command_parts))
+            }
+            "AcceptContract" => {
+                if command_parts.len() == 3 {
+                    if let Ok(contract_id) = command_parts[1].parse::<u64>() {
+                        return Ok(ExternalCommands::Universe(ExternalUniverseEventType::AcceptContract { contract_id, contractor: command_parts[2].to_string() }));
+                    }
+                }
+                Err(format!("AcceptContract need contract_id contractor_name. Got {:?}", command_parts))
+            }
+            "GetPrices" => {
+                if command_parts.len() == 3 {
+                    match command_parts[1] {
+                        "Construct" => return Ok(ExternalCommands::Universe(ExternalUniverseEventType::GetPrices(PriceScope::Construct(command_parts[2].to_string())))),
+                        "Sector" => {
+                            if let Some(sector_position) = Self::parse_sector_position(command_parts[2]) {
+                                return Ok(ExternalCommands::Universe(ExternalUniverseEventType::GetPrices(PriceScope::Sector(sector_position))));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Err(format!("GetPrices need Construct construct_name or Sector 1-2-3. Got {:?}", command_parts))
+            }
+            "AddSectorLink" | "RemoveSectorLink" => {
+                if command_parts.len() == 3 {
+                    if let (Some(from), Some(to)) = (Self::parse_sector_position(command_parts[1]), Self::parse_sector_position(command_parts[2])) {
+                        let universe_event = if command_parts[0].eq("AddSectorLink") {
+                            ExternalUniverseEventType::AddSectorLink { from, to }
+                        } else {
+                            ExternalUniverseEventType::RemoveSectorLink { from, to }
+                        };
+                        return Ok(ExternalCommands::Universe(universe_event));
+                    }
+                }
+                Err(format!("{} need two sector positions like 1-2-3 4-5-6. Got {:?}", command_parts[0], command_parts))
+            }
+            "GetContracts" => {
+                Ok(ExternalCommands::Universe(ExternalUniverseEventType::GetContracts))
+            }
+            "TransferCredits" => {
+                if command_parts.len() == 6 {
+                    if let (Some(payer), Some(payee), Ok(amount)) = (Self::parse_wallet_id(command_parts[1], command_parts[2]), Self::parse_wallet_id(command_parts[3], command_parts[4]), command_parts[5].parse::<u64>()) {
+                        return Ok(ExternalCommands::Universe(ExternalUniverseEventType::TransferCredits { payer, payee, amount }));
+                    }
+                }
+                Err(format!("TransferCredits need Owner|Construct payer_name Owner|Construct payee_name amount. Got {:?}", command_parts))
+            }
             "Undock" => {
                 if command_parts.len() > 1 {
                     return Ok(ExternalCommands::Construct(command_parts[1].to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Undock)));
@@ -233,9 +517,15 @@ impl ExternalCommands {
 
 #[cfg(test)]
 mod tests_int {
-    use crate::construct::construct::ExternalConstructEventType;
+    use crate::breakpoint::{Breakpoint, BreakpointCondition};
+    use crate::construct::construct::{AllocationMode, ExternalConstructEventType};
     use crate::construct::construct_position::ExternalConstructPositionEventType;
+    use crate::construct::production_module::OrderTarget;
+    use crate::contracts::OfPostContract;
+    use crate::credits::{Payment, WalletId};
     use crate::external_commands::{Amount, ExternalCommands};
+    use crate::market::OrderSide;
+    use crate::pricing::PriceScope;
     use crate::my_little_universe::{ExternalUniverseEventType, OfMove, OfTransferCargo};
     use crate::products::Product;
     use crate::save_load::ExternalSaveLoad;
@@ -254,9 +544,65 @@ mod tests_int {
                    ExternalCommands::try_from(&"Time SetSpeed 23".to_string()).unwrap());
         assert_eq!(ExternalCommands::Time(ExternalTimeEventType::GetTimeStackState { include_stack: true }),
                    ExternalCommands::try_from(&"Time GetTimeStackState".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(
+            "full".to_string(),
+            BreakpointCondition::StorageAtLeast { construct_name: "name".to_string(), product: Product::ware("Ores"), amount: 400 },
+        ))), ExternalCommands::try_from(&"Time AddBreakpoint full StorageAtLeast name Ores 400".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(
+            "empty".to_string(),
+            BreakpointCondition::StorageAtMost { construct_name: "name".to_string(), product: Product::ware("PowerCells"), amount: 0 },
+        ))), ExternalCommands::try_from(&"Time AddBreakpoint empty StorageAtMost name PowerCells 0".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(
+            "stuck".to_string(),
+            BreakpointCondition::OutputStored { construct_name: "name".to_string(), module_name: "PowerToOre".to_string() },
+        ))), ExternalCommands::try_from(&"Time AddBreakpoint stuck OutputStored name PowerToOre".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(Breakpoint::new(
+            "arrived".to_string(),
+            BreakpointCondition::InSector { construct_name: "name".to_string(), sector_position: SectorPosition::new(1, 2, 3) },
+        ))), ExternalCommands::try_from(&"Time AddBreakpoint arrived InSector name 1-2-3".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"Time AddBreakpoint arrived InSector name 1-2".to_string()).is_err());
+        assert_eq!(ExternalCommands::Time(ExternalTimeEventType::RemoveBreakpoint("arrived".to_string())),
+                   ExternalCommands::try_from(&"Time RemoveBreakpoint arrived".to_string()).unwrap());
 
         assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::GetConstructState { include_stack: true }),
                    ExternalCommands::try_from(&"Construct name GetConstructState".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetModulePriority { module_name: "PowerToOre".to_string(), priority: 3 }),
+                   ExternalCommands::try_from(&"Construct name SetModulePriority PowerToOre 3".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetModuleEnabled { module_name: "PowerToOre".to_string(), enabled: false }),
+                   ExternalCommands::try_from(&"Construct name DisableModule PowerToOre".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetModuleEnabled { module_name: "PowerToOre".to_string(), enabled: true }),
+                   ExternalCommands::try_from(&"Construct name EnableModule PowerToOre".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetAllocationMode(AllocationMode::FairShare)),
+                   ExternalCommands::try_from(&"Construct name SetAllocationMode FairShare".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"Construct name SetAllocationMode Random".to_string()).is_err());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetPowerSharing(true)),
+                   ExternalCommands::try_from(&"Construct name SetPowerSharing true".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"Construct name SetPowerSharing maybe".to_string()).is_err());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetMaintenance(false)),
+                   ExternalCommands::try_from(&"Construct name SetMaintenance false".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::PostMarketOrder { side: OrderSide::Sell, product: Product::ware("Ores"), quantity: 20, limit_price: 3 }),
+                   ExternalCommands::try_from(&"Construct name Sell Ores 20 3".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"Construct name Buy Ores 20".to_string()).is_err());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::PostMarketOrder { side: OrderSide::Sell, product: Product::module_named("PowerToOre"), quantity: 1, limit_price: 30 }),
+                   ExternalCommands::try_from(&"Construct name Sell Module:PowerToOre 1 30".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"Construct name Sell Module: 1 30".to_string()).is_err());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::CancelMarketOrder { order_id: 2 }),
+                   ExternalCommands::try_from(&"Construct name CancelMarketOrder 2".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetOwner(Some("alice".to_string()))),
+                   ExternalCommands::try_from(&"Construct name SetOwner alice".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::SetOwner(None)),
+                   ExternalCommands::try_from(&"Construct name RemoveOwner".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::AddProductionOrder { module_name: "Factory".to_string(), recipe_id: "PowerToOre".to_string(), target: OrderTarget::Batches(5) }),
+                   ExternalCommands::try_from(&"Construct name AddProductionOrder Factory PowerToOre Batches 5".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::AddProductionOrder { module_name: "Factory".to_string(), recipe_id: "PowerToOre".to_string(), target: OrderTarget::UntilStock(Amount::new(Product::ware("Ores"), 100)) }),
+                   ExternalCommands::try_from(&"Construct name AddProductionOrder Factory PowerToOre UntilStock Ores 100".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"Construct name AddProductionOrder Factory PowerToOre UntilStock 100".to_string()).is_err());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::RemoveProductionOrder { module_name: "Factory".to_string(), index: 1 }),
+                   ExternalCommands::try_from(&"Construct name RemoveProductionOrder Factory 1".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::BuildConstruct { module_name: "Yard".to_string(), blueprint_id: "Transport".to_string(), construct_name: "hauler".to_string() }),
+                   ExternalCommands::try_from(&"Construct name BuildConstruct Yard Transport hauler".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("name".to_string(), ExternalConstructEventType::UpgradeModule { module_name: "PowerToOre".to_string() }),
+                   ExternalCommands::try_from(&"Construct name UpgradeModule PowerToOre".to_string()).unwrap());
 
         assert_eq!(ExternalCommands::Sector(SectorPosition::new(1, 1, 1), ExternalSectorEventType::GetSectorState),
                    ExternalCommands::try_from(&"Sector 1-1-1 GetSectorState".to_string()).unwrap());
@@ -275,10 +621,23 @@ mod tests_int {
         );
         assert_eq!(
             ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
-                OfTransferCargo::new("the_construct_1".to_string(), "the_construct_2".to_string(), Amount::new(Product::Ores, 25))
+                OfTransferCargo::new("the_construct_1".to_string(), "the_construct_2".to_string(), Amount::new(Product::ware("Ores"), 25))
             )),
             ExternalCommands::try_from(&"TransferCargo the_construct_1 the_construct_2 Ores 25".to_string()).unwrap()
         );
+        assert_eq!(
+            ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
+                OfTransferCargo::new("the_construct_1".to_string(), "the_construct_2".to_string(), Amount::new(Product::ware("Ores"), 25)).with_price_per_unit(3)
+            )),
+            ExternalCommands::try_from(&"TransferCargo the_construct_1 the_construct_2 Ores 25 3".to_string()).unwrap()
+        );
+        assert!(ExternalCommands::try_from(&"TransferCargo the_construct_1 the_construct_2 Ores 25 free".to_string()).is_err());
+        assert_eq!(
+            ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(
+                OfTransferCargo::new("the_construct_1".to_string(), "the_construct_2".to_string(), Amount::new(Product::module_named("PowerToOre"), 2))
+            )),
+            ExternalCommands::try_from(&"TransferCargo the_construct_1 the_construct_2 Module:PowerToOre 2".to_string()).unwrap()
+        );
         assert_eq!(
             ExternalCommands::Construct(
                 "the_construct_1".to_string(),
@@ -295,6 +654,50 @@ mod tests_int {
             ),
             ExternalCommands::try_from(&"Undock the_construct_1".to_string()).unwrap()
         );
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetProductionStats),
+                   ExternalCommands::try_from(&"GetProductionStats".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetEconomyBalance(None)),
+                   ExternalCommands::try_from(&"GetEconomyBalance".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetEconomyBalance(Some(SectorPosition::new(1, 2, 3)))),
+                   ExternalCommands::try_from(&"GetEconomyBalance 1-2-3".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("the_construct_1".to_string(), ExternalConstructEventType::InstallModule { module_name: "PowerToOre".to_string(), installed_name: None, price: None }),
+                   ExternalCommands::try_from(&"InstallModule the_construct_1 PowerToOre".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("the_construct_1".to_string(), ExternalConstructEventType::InstallModule { module_name: "PowerToOre".to_string(), installed_name: None, price: Some(Payment::new(WalletId::Owner("alice".to_string()), 100)) }),
+                   ExternalCommands::try_from(&"InstallModule the_construct_1 PowerToOre Owner alice 100".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("the_construct_1".to_string(), ExternalConstructEventType::InstallModule { module_name: "PowerToOre".to_string(), installed_name: Some("SecondMine".to_string()), price: None }),
+                   ExternalCommands::try_from(&"InstallModule the_construct_1 PowerToOre SecondMine".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Construct("the_construct_1".to_string(), ExternalConstructEventType::InstallModule { module_name: "PowerToOre".to_string(), installed_name: Some("SecondMine".to_string()), price: Some(Payment::new(WalletId::Construct("seller".to_string()), 5)) }),
+                   ExternalCommands::try_from(&"InstallModule the_construct_1 PowerToOre SecondMine Construct seller 5".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"InstallModule the_construct_1 PowerToOre Owner alice".to_string()).is_err());
+        assert_eq!(ExternalCommands::Construct("the_construct_1".to_string(), ExternalConstructEventType::UninstallModule { module_name: "PowerToOre".to_string() }),
+                   ExternalCommands::try_from(&"UninstallModule the_construct_1 PowerToOre".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"InstallModule the_construct_1".to_string()).is_err());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetBalance(WalletId::Owner("alice".to_string()))),
+                   ExternalCommands::try_from(&"GetBalance Owner alice".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetLedger(WalletId::Construct("the_construct_1".to_string()))),
+                   ExternalCommands::try_from(&"GetLedger Construct the_construct_1".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GrantCredits { payee: WalletId::Owner("alice".to_string()), amount: 100 }),
+                   ExternalCommands::try_from(&"GrantCredits Owner alice 100".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::TransferCredits { payer: WalletId::Owner("alice".to_string()), payee: WalletId::Construct("the_construct_1".to_string()), amount: 40 }),
+                   ExternalCommands::try_from(&"TransferCredits Owner alice Construct the_construct_1 40".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"GetBalance Bank alice".to_string()).is_err());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::PostContract(OfPostContract::new("the_construct_1".to_string(), Product::ware("Ores"), 20, 15, 100, 25))),
+                   ExternalCommands::try_from(&"PostContract the_construct_1 Ores 20 15 100 25".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"PostContract the_construct_1 Ores 20 15 100".to_string()).is_err());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::AcceptContract { contract_id: 3, contractor: "the_construct_2".to_string() }),
+                   ExternalCommands::try_from(&"AcceptContract 3 the_construct_2".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetPrices(PriceScope::Construct("the_construct_1".to_string()))),
+                   ExternalCommands::try_from(&"GetPrices Construct the_construct_1".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetPrices(PriceScope::Sector(SectorPosition::new(1, 2, 3)))),
+                   ExternalCommands::try_from(&"GetPrices Sector 1-2-3".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"GetPrices Sector the_construct_1".to_string()).is_err());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::AddSectorLink { from: SectorPosition::new(1, 1, 1), to: SectorPosition::new(1, 1, 2) }),
+                   ExternalCommands::try_from(&"AddSectorLink 1-1-1 1-1-2".to_string()).unwrap());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::RemoveSectorLink { from: SectorPosition::new(1, 1, 1), to: SectorPosition::new(1, 1, 2) }),
+                   ExternalCommands::try_from(&"RemoveSectorLink 1-1-1 1-1-2".to_string()).unwrap());
+        assert!(ExternalCommands::try_from(&"AddSectorLink 1-1-1".to_string()).is_err());
+        assert_eq!(ExternalCommands::Universe(ExternalUniverseEventType::GetContracts),
+                   ExternalCommands::try_from(&"GetContracts".to_string()).unwrap());
 
         assert_eq!(ExternalCommands::Save(ExternalSaveLoad::TheUniverse),
                    ExternalCommands::try_from(&"Save TheUniverse".to_string()).unwrap());
@@ -1,35 +1,69 @@
-use std::{thread, time};
+use std::thread;
 use std::sync::mpsc;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
 
 use crate::external_commands::{ExternalCommandReturnValues, ExternalCommands};
 use crate::MainConfig;
 use crate::save_load::load_or_create_universe;
+use crate::time::TimeClock;
+
+type Request = (ExternalCommands, Sender<ExternalCommandReturnValues>);
 
 // channel_getter is one channel to receive new channels.
 // Then the loop will listen for events from that channel to execute.
-fn game_loop(channel_getter: Receiver<Channel>, config: MainConfig) {
+// The loop sleeps until either an event arrives or the next turn is due.
+fn game_loop(channel_getter: Receiver<Channel>, config: MainConfig, clock: TimeClock) {
+    let (request_sender, request_getter): (Sender<Request>, Receiver<Request>) = mpsc::channel();
+    forward_channels(channel_getter, request_sender);
+
     thread::spawn(move || {
         let mut universe = load_or_create_universe(&config);
+        universe.set_clock(clock);
         println!("Loaded universe with name {}", universe.universe_name());
-        let mut channels = Vec::new();
 
         loop {
-            for channel in channel_getter.try_recv() {
-                channels.push(channel);
-            }
-
-            for channel in &channels {
-                for event in channel.getter.try_recv() {
-                    channel.returner
-                        .send(universe.handle_event(event))
-                        .expect("Failed sending event in gameloop.")
+            let request = match universe.time().milli_secs_until_next_turn() {
+                Some(milli_secs) => {
+                    match request_getter.recv_timeout(Duration::from_millis(milli_secs)) {
+                        Ok(request) => Some(request),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => break
+                    }
                 }
-            }
+                None => {
+                    match request_getter.recv() {
+                        Ok(request) => Some(request),
+                        Err(_) => break
+                    }
+                }
+            };
 
+            // A turn that became due while waiting is executed before the event is answered.
             universe.request_execute_turn();
 
-            thread::sleep(time::Duration::from_millis(10))
+            if let Some((event, returner)) = request {
+                returner
+                    .send(universe.handle_event(event))
+                    .expect("Failed sending event in gameloop.")
+            }
+        }
+        println!("Stopped universe with name {}, no one can send it events anymore.", universe.universe_name());
+    });
+}
+
+// Every channel gets a thread forwarding its events, so the game loop only has to wait on one receiver.
+fn forward_channels(channel_getter: Receiver<Channel>, request_sender: Sender<Request>) {
+    thread::spawn(move || {
+        for channel in channel_getter.iter() {
+            let request_sender = request_sender.clone();
+            thread::spawn(move || {
+                for event in channel.getter.iter() {
+                    if request_sender.send((event, channel.returner.clone())).is_err() {
+                        break;
+                    }
+                }
+            });
         }
     });
 }
@@ -57,19 +91,23 @@ impl Channel {
 
 impl Communicator {
     pub fn new(config: &MainConfig) -> Self {
+        Self::new_with_clock(config, TimeClock::system())
+    }
+
+    pub fn new_with_clock(config: &MainConfig, clock: TimeClock) -> Self {
         let (channel_sender, channel_getter): (Sender<Channel>, Receiver<Channel>) = mpsc::channel();
         let stack = Communicator { channel_sender };
-        game_loop(channel_getter, config.clone());
+        game_loop(channel_getter, config.clone(), clock);
         stack
     }
 }
 
 #[cfg(test)]
 mod tests_int {
-    use std::sync::mpsc;
+    use std::sync::{Arc, mpsc};
     use std::sync::mpsc::{Receiver, Sender};
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     use crate::construct::construct::ExternalConstructEventType;
     use crate::construct::construct::ConstructEvenReturnType::ConstructState;
@@ -80,7 +118,7 @@ mod tests_int {
     use crate::gameloop::{Channel, Communicator};
     use crate::MainConfig;
     use crate::products::Product;
-    use crate::time::ExternalTimeEventType;
+    use crate::time::{ExternalTimeEventType, ManualClock, TimeClock, TimeStackState};
     use crate::time::TimeEventReturnType::{Received, StackState};
 
     #[test]
@@ -123,7 +161,8 @@ mod tests_int {
         let (main_to_universe_sender, main_to_universe_receiver): (Sender<ExternalCommands>, Receiver<ExternalCommands>) = mpsc::channel();
         let (universe_to_main_sender, universe_to_main_receiver): (Sender<ExternalCommandReturnValues>, Receiver<ExternalCommandReturnValues>) = mpsc::channel();
 
-        let time_stack = Communicator::new(&main_config);
+        let clock = Arc::new(ManualClock::new(0));
+        let time_stack = Communicator::new_with_clock(&main_config, TimeClock::new(clock.clone()));
         let channel = Channel {
             getter: main_to_universe_receiver,
             returner: universe_to_main_sender,
@@ -139,13 +178,52 @@ mod tests_int {
         send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::SetSpeed(1000)));
         send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::StartUntilTurn(2)));
         check_turn(&main_to_universe_sender, &universe_to_main_receiver, 1);
-        thread::sleep(Duration::from_secs(1));
+        clock.advance(999);
+        check_turn(&main_to_universe_sender, &universe_to_main_receiver, 1);
+        clock.advance(1);
         check_turn(&main_to_universe_sender, &universe_to_main_receiver, 2);
         send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::SetSpeed(0)));
         send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::StartUntilTurn(3)));
         check_turn(&main_to_universe_sender, &universe_to_main_receiver, 3);
     }
 
+    #[test]
+    fn loop_wakes_for_events_and_turns() {
+        let main_config = MainConfig {
+            address: "random".to_string(),
+            universe_name: "testing".to_string(),
+            config_name: "default".to_string(),
+        };
+
+        let (main_to_universe_sender, main_to_universe_receiver): (Sender<ExternalCommands>, Receiver<ExternalCommands>) = mpsc::channel();
+        let (universe_to_main_sender, universe_to_main_receiver): (Sender<ExternalCommandReturnValues>, Receiver<ExternalCommandReturnValues>) = mpsc::channel();
+
+        let clock = Arc::new(ManualClock::new(0));
+        let time_stack = Communicator::new_with_clock(&main_config, TimeClock::new(clock.clone()));
+        let channel = Channel {
+            getter: main_to_universe_receiver,
+            returner: universe_to_main_sender,
+        };
+        time_stack.channel_sender.send(channel).expect("The game loop should accept the channel");
+
+        // The next turn is a minute away, an event is still answered right away.
+        send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::SetSpeed(60 * 1000)));
+        send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::Start));
+        let sent = Instant::now();
+        check_turn(&main_to_universe_sender, &universe_to_main_receiver, 0);
+        assert!(sent.elapsed() < Duration::from_millis(500), "Answering took {:?}", sent.elapsed());
+
+        // Without any event the loop wakes up when the turn is due, so the turn has the timestamp of that moment.
+        send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::SetSpeed(50)));
+        send_and_wait(&main_to_universe_sender, &universe_to_main_receiver, ExternalCommands::Time(ExternalTimeEventType::StartUntilTurn(1)));
+        clock.advance(50);
+        thread::sleep(Duration::from_millis(500));
+        clock.advance(1000);
+        let state = get_time_stack_state(&main_to_universe_sender, &universe_to_main_receiver);
+        assert_eq!(1, state.turn());
+        assert_eq!(50, state.last_turn_timestamp());
+    }
+
     #[test]
     fn next_turn_without_limit() {
         let main_config = MainConfig {
@@ -245,6 +323,7 @@ mod tests_int {
                                             None
                                         }
                                     }
+                                    _ => None
                                 })
                             .next()
                             .unwrap();
@@ -253,8 +332,8 @@ mod tests_int {
                         assert_eq!(1, production.production_time());
                         assert_eq!(false, production.stored_output());
                         assert_eq!(false, production.stored_input());
-                        assert_eq!(&vec![Amount::new(Product::PowerCells, 1); 1], production.input());
-                        assert_eq!(&vec![Amount::new(Product::Ores, 2); 1], production.output());
+                        assert_eq!(&vec![Amount::new(Product::ware("PowerCells"), 1); 1], production.input());
+                        assert_eq!(&vec![Amount::new(Product::ware("Ores"), 2); 1], production.output());
                     }
                     _ => assert!(false)
                 }
@@ -273,8 +352,8 @@ mod tests_int {
             ConstructEvent(construct_return) => {
                 match construct_return {
                     ConstructState(construct_state) => {
-                        assert_eq!(expected_first_input_current_storage, construct_state.current_storage().get(&Product::PowerCells));
-                        assert_eq!(expected_first_output_current_storage, construct_state.current_storage().get(&Product::Ores));
+                        assert_eq!(expected_first_input_current_storage, construct_state.current_storage().get(&Product::ware("PowerCells")));
+                        assert_eq!(expected_first_output_current_storage, construct_state.current_storage().get(&Product::ware("Ores")));
                         assert_eq!(production_trigger_time, if let Some(Production(production_module)) = construct_state.modules().get(0) { production_module.production_trigger_time() } else { unreachable!() });
                     }
                     _ => assert!(false)
@@ -294,19 +373,18 @@ mod tests_int {
     }
 
     fn check_turn(main_to_universe_sender: &Sender<ExternalCommands>, universe_to_main_receiver: &Receiver<ExternalCommandReturnValues>, expected_turn_count: u64) {
+        assert_eq!(expected_turn_count, get_time_stack_state(main_to_universe_sender, universe_to_main_receiver).turn());
+    }
+
+    fn get_time_stack_state(main_to_universe_sender: &Sender<ExternalCommands>, universe_to_main_receiver: &Receiver<ExternalCommandReturnValues>) -> TimeStackState {
         match main_to_universe_sender.send(ExternalCommands::Time(ExternalTimeEventType::GetTimeStackState { include_stack: true })) {
             Err(e) => println!("Sender errored: {}", e),
             _ => {}
         }
 
         match universe_to_main_receiver.recv_timeout(Duration::from_secs(1)).unwrap() {
-            ExternalCommandReturnValues::Time(time_return) => {
-                match time_return {
-                    StackState(state) => assert_eq!(expected_turn_count, state.turn()),
-                    _ => assert!(false)
-                }
-            }
-            _ => assert!(false)
+            ExternalCommandReturnValues::Time(StackState(state)) => state,
+            return_value => panic!("Expected the time stack state, got {:?}", return_value)
         }
     }
 
@@ -0,0 +1,82 @@
+use std::{env, fs};
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+pub use crate::external_commands::{ExternalCommandReturnValues, ExternalCommands};
+
+pub mod time;
+pub mod gameloop;
+pub mod products;
+pub mod external_commands;
+pub mod save_load;
+pub mod my_little_universe;
+pub mod universe_generator;
+pub mod construct;
+pub mod construct_module;
+pub mod sector;
+pub mod simulation;
+pub mod breakpoint;
+pub mod turn_phase;
+pub mod recipes;
+pub mod module_levels;
+pub mod blueprints;
+pub mod economy;
+pub mod power;
+pub mod wear;
+pub mod market;
+pub mod credits;
+pub mod contracts;
+pub mod pricing;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MainConfig {
+    address: String,
+    #[serde(default)]
+    universe_name: String,
+    #[serde(default)]
+    config_name: String,
+}
+
+impl MainConfig {
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+    pub fn universe_name(&self) -> &str {
+        &self.universe_name
+    }
+    pub fn config_name(&self) -> &str {
+        &self.config_name
+    }
+}
+
+pub fn read_main_config_file() -> MainConfig {
+    let args: Vec<String> = env::args().collect();
+
+    let universe_name = if args.len() > 1 {
+        &args[1]
+    } else {
+        panic!("First argument of starting the game is the universe_name, got: {:?}", args)
+    };
+
+    let config_name = if args.len() > 2 {
+        &args[2]
+    } else {
+        "default/"
+    };
+
+    let config_folder = "./config/".to_string().add(config_name);
+
+    let main_config_path = config_folder.to_string().add("/main.json");
+    println!("Using main config main_config_path: {}", main_config_path);
+
+    let main_setup_config = fs::read_to_string(main_config_path)
+        .expect("Something went wrong reading the file");
+
+    let mut main_config: MainConfig = serde_json::from_str(main_setup_config.as_str()).unwrap();
+
+    main_config.universe_name = universe_name.clone();
+    main_config.config_name = config_name.to_string();
+
+    main_config
+}
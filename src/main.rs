@@ -1,50 +1,16 @@
 extern crate core;
 
-use std::{env, fs};
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
-use std::ops::Add;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
 use std::time::Duration;
 
-use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::external_commands::{ExternalCommandReturnValues, ExternalCommands};
-use crate::gameloop::{Channel, Communicator};
-
-mod time;
-mod gameloop;
-mod products;
-mod external_commands;
-mod save_load;
-mod my_little_universe;
-mod universe_generator;
-mod construct;
-pub mod construct_module;
-mod sector;
-
-#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-pub struct MainConfig {
-    address: String,
-    #[serde(default)]
-    universe_name: String,
-    #[serde(default)]
-    config_name: String,
-}
-
-impl MainConfig {
-    pub fn address(&self) -> &str {
-        &self.address
-    }
-    pub fn universe_name(&self) -> &str {
-        &self.universe_name
-    }
-    pub fn config_name(&self) -> &str {
-        &self.config_name
-    }
-}
+use my_little_universe::{MainConfig, read_main_config_file};
+use my_little_universe::external_commands::{ExternalCommandReturnValues, ExternalCommands};
+use my_little_universe::gameloop::{Channel, Communicator};
 
 fn main() {
     let main_config = read_main_config_file();
@@ -67,37 +33,6 @@ fn main() {
     drop(listener);
 }
 
-fn read_main_config_file() -> MainConfig {
-    let args: Vec<String> = env::args().collect();
-
-    let universe_name = if args.len() > 1 {
-        &args[1]
-    } else {
-        panic!("First argument of starting the game is the universe_name, got: {:?}", args)
-    };
-
-    let config_name = if args.len() > 2 {
-        &args[2]
-    } else {
-        "default/"
-    };
-
-    let config_folder = "./config/".to_string().add(config_name);
-
-    let main_config_path = config_folder.to_string().add("/main.json");
-    println!("Using main config main_config_path: {}", main_config_path);
-
-    let main_setup_config = fs::read_to_string(main_config_path)
-        .expect("Something went wrong reading the file");
-
-    let mut main_config: MainConfig = serde_json::from_str(main_setup_config.as_str()).unwrap();
-
-    main_config.universe_name = universe_name.clone();
-    main_config.config_name = config_name.to_string();
-
-    main_config
-}
-
 fn handle_request(main_to_universe_sender: &Sender<ExternalCommands>, universe_to_main_receiver: &Receiver<ExternalCommandReturnValues>, stream: &mut TcpStream) {
     // connection succeeded
     let mut buffer = [0; 1024];
@@ -150,7 +85,7 @@ fn handle_request(main_to_universe_sender: &Sender<ExternalCommands>, universe_t
 }
 
 fn setup_game(config: &MainConfig) -> (TcpListener, Sender<ExternalCommands>, Receiver<ExternalCommandReturnValues>) {
-    let listener = TcpListener::bind(&config.address).unwrap();
+    let listener = TcpListener::bind(config.address()).unwrap();
 
     let communicator = Communicator::new(config);
     let (main_to_universe_sender, main_to_universe_receiver): (Sender<ExternalCommands>, Receiver<ExternalCommands>) = mpsc::channel();
@@ -162,6 +97,6 @@ fn setup_game(config: &MainConfig) -> (TcpListener, Sender<ExternalCommands>, Re
         Err(e) => println!("Sender errored: {}", e),
         _ => {}
     }
-    println!("Game is ready and listening on: {}", &config.address);
+    println!("Game is ready and listening on: {}", config.address());
     (listener, main_to_universe_sender, universe_to_main_receiver)
 }
@@ -0,0 +1,305 @@
+use std::cmp::min;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::amount::Amount;
+use crate::construct::construct::{ConstructEvenReturnType, ConstructEventType, InternalConstructEventType};
+use crate::credits::TransactionKind;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::Product;
+use crate::sector::SectorPosition;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+// The limit price is the most a buyer pays per unit, or the least a seller accepts per unit.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MarketOrder {
+    id: u64,
+    side: OrderSide,
+    product: Product,
+    quantity: u32,
+    limit_price: u32,
+}
+
+impl MarketOrder {
+    pub fn new(id: u64, side: OrderSide, product: Product, quantity: u32, limit_price: u32) -> Self {
+        MarketOrder { id, side, product, quantity, limit_price }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    pub fn side(&self) -> OrderSide {
+        self.side
+    }
+    pub fn product(&self) -> &Product {
+        &self.product
+    }
+    pub fn quantity(&self) -> u32 {
+        self.quantity
+    }
+    pub fn limit_price(&self) -> u32 {
+        self.limit_price
+    }
+
+    pub(crate) fn fill(&mut self, quantity: u32) {
+        self.quantity -= quantity;
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Trade {
+    turn: u64,
+    buyer: String,
+    seller: String,
+    product: Product,
+    quantity: u32,
+    price: u32,
+}
+
+impl Trade {
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+    pub fn buyer(&self) -> &str {
+        &self.buyer
+    }
+    pub fn seller(&self) -> &str {
+        &self.seller
+    }
+    pub fn product(&self) -> &Product {
+        &self.product
+    }
+    pub fn quantity(&self) -> u32 {
+        self.quantity
+    }
+    pub fn price(&self) -> u32 {
+        self.price
+    }
+}
+
+struct BookEntry {
+    construct_name: String,
+    order: MarketOrder,
+}
+
+// The orders for one product in one group.
+#[derive(Default)]
+struct Book {
+    buys: Vec<BookEntry>,
+    sells: Vec<BookEntry>,
+}
+
+impl MyLittleUniverse {
+    // Constructs trade with the constructs in their group, docked constructs trade from the group of their host.
+    // Constructs in transit do not trade until they arrive.
+    pub(crate) fn match_market_orders(&mut self, current_turn: &u64) {
+        let mut books: HashMap<(SectorPosition, usize, Product), Book> = HashMap::new();
+        for construct_name in self.construct_names_in_turn_order() {
+            let position = match self.get_sector_position(construct_name.clone()) {
+                Some(position) => position.clone(),
+                None => continue
+            };
+            for order in self.constructs.get(&construct_name).unwrap().market_orders() {
+                let book = books.entry((position.sector_position().clone(), position.group_address(), order.product.clone())).or_default();
+                let entry = BookEntry { construct_name: construct_name.clone(), order: order.clone() };
+                match order.side {
+                    OrderSide::Buy => book.buys.push(entry),
+                    OrderSide::Sell => book.sells.push(entry),
+                }
+            }
+        }
+
+        // Sorted, so the trades are in the same order every time.
+        let mut books: Vec<(String, Book)> = books.into_iter()
+            .map(|((sector_position, group_address, product), book)| (format!("{:?} {} {}", sector_position, group_address, product), book))
+            .collect();
+        books.sort_by(|(key, _), (other_key, _)| key.cmp(other_key));
+        // Highest bid and lowest ask first, the name order of the constructs breaks ties.
+        for (_, book) in &mut books {
+            book.buys.sort_by_key(|entry| std::cmp::Reverse(entry.order.limit_price));
+            book.sells.sort_by_key(|entry| entry.order.limit_price);
+        }
+
+        let mut trades = Vec::new();
+        for (_, mut book) in books {
+            for buy in &mut book.buys {
+                for sell in &mut book.sells {
+                    if sell.order.limit_price > buy.order.limit_price || buy.order.quantity == 0 {
+                        break;
+                    }
+                    if sell.construct_name.eq(&buy.construct_name) || sell.order.quantity == 0 {
+                        continue;
+                    }
+                    let quantity = self.settle(current_turn, &sell.construct_name, &buy.construct_name, &buy.order.product, min(buy.order.quantity, sell.order.quantity), sell.order.limit_price);
+                    if quantity > 0 {
+                        buy.order.fill(quantity);
+                        sell.order.fill(quantity);
+                        trades.push(Trade {
+                            turn: *current_turn,
+                            buyer: buy.construct_name.clone(),
+                            seller: sell.construct_name.clone(),
+                            product: buy.order.product.clone(),
+                            quantity,
+                            price: sell.order.limit_price,
+                        });
+                    }
+                }
+            }
+            for entry in book.buys.iter().chain(book.sells.iter()) {
+                self.constructs.get_mut(&entry.construct_name).unwrap().update_market_order(&entry.order);
+            }
+        }
+        self.trades = trades;
+    }
+
+    // Moves the cargo like TransferCargo does, limited to what the seller has, what the buyer has room for and what the buyer can pay.
+    // The buyer pays the price of what is moved to the seller, constructs sharing a wallet trade for free.
+    fn settle(&mut self, current_turn: &u64, seller_name: &str, buyer_name: &str, product: &Product, quantity: u32, price: u32) -> u32 {
+        let buyer_wallet = self.wallet_of(buyer_name);
+        let seller_wallet = self.wallet_of(seller_name);
+        let buyer = self.constructs.get(buyer_name).unwrap();
+        let room = buyer.storage_capacity().free_volume_for(buyer.current_storage(), product.category()) / product.volume();
+        let mut quantity = min(quantity, room);
+        if buyer_wallet.ne(&seller_wallet) && price > 0 {
+            let affordable = self.credits.balance(&buyer_wallet) / u64::from(price);
+            quantity = min(quantity, u32::try_from(affordable).unwrap_or(u32::MAX));
+        }
+        if quantity == 0 {
+            return 0;
+        }
+
+        let unloaded = match self.constructs.get_mut(seller_name).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(Amount::new(product.clone(), quantity)))) {
+            ConstructEvenReturnType::RequestUnloadProcessed(amount) => amount,
+            _ => 0
+        };
+        if unloaded == 0 {
+            return 0;
+        }
+        let loaded = match self.constructs.get_mut(buyer_name).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(product.clone(), unloaded)))) {
+            ConstructEvenReturnType::RequestLoadProcessed(amount) => amount,
+            _ => 0
+        };
+        if buyer_wallet.ne(&seller_wallet) {
+            self.credits.transfer(*current_turn, &buyer_wallet, &seller_wallet, u64::from(price) * u64::from(loaded), TransactionKind::Trade)
+                .expect("The quantity was limited to what the buyer can pay.");
+        }
+        loaded
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::credits::{TransactionKind, WalletId};
+    use crate::market::OrderSide;
+    use crate::my_little_universe::{ExternalUniverseEventType, OfMove};
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::{add_distant_sector, generate_simple_universe};
+
+    #[test]
+    fn orders_in_the_same_group_are_matched() {
+        let mut universe = generate_simple_universe("market".to_string(), &read_recipe_catalog("default"));
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 30))));
+        universe.constructs.get_mut("The_base_2").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 30))));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GrantCredits { payee: WalletId::Construct("The_base_1".to_string()), amount: 1000 }));
+
+        let post = |universe: &mut crate::my_little_universe::MyLittleUniverse, construct_name: &str, side: OrderSide, quantity: u32, limit_price: u32| {
+            universe.handle_event(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::PostMarketOrder { side, product: Product::ware("Metals"), quantity, limit_price }))
+        };
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::MarketOrderPosted(0)), post(&mut universe, "The_base_1", OrderSide::Buy, 50, 10));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::MarketOrderPosted(0)), post(&mut universe, "transport", OrderSide::Sell, 40, 8));
+        // Too expensive, and the other base is in another sector.
+        post(&mut universe, "transport", OrderSide::Sell, 10, 12);
+        post(&mut universe, "The_base_2", OrderSide::Sell, 30, 1);
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("A market order needs a quantity above zero.".to_string())), post(&mut universe, "transport", OrderSide::Sell, 0, 1));
+
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        assert!(universe.request_execute_turn());
+
+        // Only the 30 metals the transport has are sold, at the asking price.
+        assert_eq!(1, universe.last_trades().len());
+        assert_eq!(30, universe.last_trades()[0].quantity());
+        assert_eq!(8, universe.last_trades()[0].price());
+        assert_eq!(Some(&30), universe.constructs().get("The_base_1").unwrap().current_storage().get(&Product::ware("Metals")));
+        assert_eq!(20, universe.constructs().get("The_base_1").unwrap().market_orders()[0].quantity());
+        assert_eq!(2, universe.constructs().get("transport").unwrap().market_orders().len());
+
+        // Filled orders are removed, cancelled orders are returned.
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 30))));
+        assert!(universe.request_execute_turn());
+        assert_eq!(10, universe.constructs().get("The_base_1").unwrap().market_orders()[0].quantity());
+        assert_eq!(1, universe.constructs().get("transport").unwrap().market_orders().len());
+        match universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::CancelMarketOrder { order_id: 1 })) {
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::MarketOrderCancelled(order)) => assert_eq!(12, order.limit_price()),
+            return_value => panic!("Expected the cancelled order, got {:?}", return_value)
+        }
+        assert!(universe.constructs().get("transport").unwrap().market_orders().is_empty());
+    }
+
+    #[test]
+    fn buyers_pay_for_their_trades() {
+        let mut universe = generate_simple_universe("market".to_string(), &read_recipe_catalog("default"));
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 30))));
+        let buyer = WalletId::Construct("The_base_1".to_string());
+        let seller = WalletId::Construct("transport".to_string());
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GrantCredits { payee: buyer.clone(), amount: 100 }));
+        universe.handle_event(ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::PostMarketOrder { side: OrderSide::Buy, product: Product::ware("Metals"), quantity: 30, limit_price: 10 }));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::PostMarketOrder { side: OrderSide::Sell, product: Product::ware("Metals"), quantity: 30, limit_price: 8 }));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        // The buyer only gets what it can pay for at the asking price.
+        assert!(universe.request_execute_turn());
+        assert_eq!(12, universe.last_trades()[0].quantity());
+        assert_eq!(4, universe.credits().balance(&buyer));
+        assert_eq!(96, universe.credits().balance(&seller));
+        assert_eq!(Some(TransactionKind::Trade), universe.credits().transactions_of(&seller).last().map(|transaction| transaction.kind()));
+
+        assert!(universe.request_execute_turn());
+        assert!(universe.last_trades().is_empty());
+        assert_eq!(Some(&18), universe.constructs().get("transport").unwrap().current_storage().get(&Product::ware("Metals")));
+
+        // Constructs of the same owner trade without paying.
+        for construct_name in ["The_base_1", "transport"] {
+            universe.handle_event(ExternalCommands::Construct(construct_name.to_string(), ExternalConstructEventType::SetOwner(Some("alice".to_string()))));
+        }
+        assert!(universe.request_execute_turn());
+        assert_eq!(18, universe.last_trades()[0].quantity());
+        assert_eq!(0, universe.credits().balance(&WalletId::Owner("alice".to_string())));
+    }
+
+    #[test]
+    fn constructs_in_transit_do_not_trade() {
+        let mut universe = generate_simple_universe("market".to_string(), &read_recipe_catalog("default"));
+        let distant = add_distant_sector(&mut universe);
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 30))));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GrantCredits { payee: WalletId::Construct("The_base_1".to_string()), amount: 1000 }));
+        universe.handle_event(ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::PostMarketOrder { side: OrderSide::Buy, product: Product::ware("Metals"), quantity: 30, limit_price: 10 }));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::PostMarketOrder { side: OrderSide::Sell, product: Product::ware("Metals"), quantity: 30, limit_price: 8 }));
+        universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), distant, Some(0)))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        // The seller left the group of the buyer, the orders stay open.
+        for _ in 0..3 {
+            assert!(universe.request_execute_turn());
+            assert!(universe.last_trades().is_empty());
+        }
+        assert_eq!(Some(&30), universe.constructs().get("transport").unwrap().current_storage().get(&Product::ware("Metals")));
+        assert_eq!(30, universe.constructs().get("transport").unwrap().market_orders()[0].quantity());
+        assert_eq!(1000, universe.credits().balance(&WalletId::Construct("The_base_1".to_string())));
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::amount::Amount;
+
+// What it costs to reach a level and what the level does, relative to the module at level 1.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ModuleLevel {
+    level: u32,
+    cost: Vec<Amount>,
+    output_percent: u32,
+    production_time_percent: u32,
+}
+
+impl ModuleLevel {
+    pub fn new(level: u32, cost: Vec<Amount>, output_percent: u32, production_time_percent: u32) -> Self {
+        ModuleLevel { level, cost, output_percent, production_time_percent }
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+    pub fn cost(&self) -> &Vec<Amount> {
+        &self.cost
+    }
+    pub fn output_percent(&self) -> u32 {
+        self.output_percent
+    }
+    pub fn production_time_percent(&self) -> u32 {
+        self.production_time_percent
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub struct ModuleLevelCatalog {
+    levels: Vec<ModuleLevel>,
+}
+
+impl ModuleLevelCatalog {
+    pub fn new(levels: Vec<ModuleLevel>) -> Self {
+        ModuleLevelCatalog { levels }
+    }
+
+    pub fn levels(&self) -> &Vec<ModuleLevel> {
+        &self.levels
+    }
+
+    pub fn get(&self, level: u32) -> Option<&ModuleLevel> {
+        self.levels.iter().find(|module_level| module_level.level == level)
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        // Every module starts at level 1, so the upgrades are level 2, 3 and so on without gaps.
+        for (index, module_level) in self.levels.iter().enumerate() {
+            let expected_level = index as u32 + 2;
+            if module_level.level != expected_level {
+                errors.push(format!("Module level {} should have been level {}.", module_level.level, expected_level));
+            }
+            if module_level.output_percent == 0 {
+                errors.push(format!("Module level {} needs an output_percent above zero.", module_level.level));
+            }
+            if module_level.production_time_percent == 0 {
+                errors.push(format!("Module level {} needs a production_time_percent above zero.", module_level.level));
+            }
+            for amount in &module_level.cost {
+                if amount.amount() == 0 {
+                    errors.push(format!("Module level {} has a cost of zero {}.", module_level.level, amount.product()));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub fn read_module_level_catalog(config_name: &str) -> ModuleLevelCatalog {
+    let config_folder = "./config/".to_string().add(config_name);
+
+    let module_levels_config_path = config_folder.to_string().add("/module_levels.json");
+    println!("Using module levels config module_levels_config_path: {}", module_levels_config_path);
+
+    let module_levels_config = fs::read_to_string(&module_levels_config_path)
+        .unwrap_or_else(|_| panic!("Something went wrong reading the file {}", module_levels_config_path));
+
+    let module_level_catalog: ModuleLevelCatalog = serde_json::from_str(module_levels_config.as_str())
+        .unwrap_or_else(|_| panic!("Something went wrong parsing the file {}", module_levels_config_path));
+
+    if let Err(errors) = module_level_catalog.validate() {
+        panic!("The module levels in {} are not valid: {:?}", module_levels_config_path, errors);
+    }
+
+    module_level_catalog
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::construct::amount::Amount;
+    use crate::module_levels::{ModuleLevel, ModuleLevelCatalog, read_module_level_catalog};
+    use crate::products::Product;
+
+    #[test]
+    fn read_default_module_levels() {
+        let module_level_catalog = read_module_level_catalog("default");
+        assert_eq!(
+            Some(&ModuleLevel::new(2, vec![Amount::new(Product::ware("Metals"), 10), Amount::new(Product::ware("PowerCells"), 10)], 200, 100)),
+            module_level_catalog.get(2)
+        );
+        assert_eq!(None, module_level_catalog.get(1));
+
+        assert_eq!(Ok(()), read_module_level_catalog("performance").validate());
+    }
+
+    #[test]
+    fn validate_module_levels() {
+        let module_level_catalog = ModuleLevelCatalog::new(vec![
+            ModuleLevel::new(2, vec![Amount::new(Product::ware("Metals"), 0)], 100, 100),
+            ModuleLevel::new(4, vec![], 0, 0),
+        ]);
+        assert_eq!(Err(vec![
+            "Module level 2 has a cost of zero Metals.".to_string(),
+            "Module level 4 should have been level 3.".to_string(),
+            "Module level 4 needs an output_percent above zero.".to_string(),
+            "Module level 4 needs a production_time_percent above zero.".to_string(),
+        ]), module_level_catalog.validate());
+    }
+}
@@ -1,21 +1,45 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{ExternalCommandReturnValues, ExternalCommands};
 use crate::construct::construct::{Construct, ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
 use crate::construct::construct_position::{ConstructPositionEventReturnType, ConstructPositionEventType, ConstructPositionSector, ConstructPositionStatus, ExternalConstructPositionEventType, InternalConstructPositionEventType};
+use crate::blueprints::BlueprintCatalog;
+use crate::construct::production_module::{OrderTarget, ProductionOrder, ProductionStats};
+use crate::construct_module::ConstructModuleType;
+use crate::economy::EconomyBalance;
 use crate::external_commands::Amount;
+use crate::module_levels::ModuleLevelCatalog;
+use crate::pricing::{PriceScope, ProductPrice};
+use crate::products::Product;
+use crate::recipes::RecipeCatalog;
 use crate::save_load::ExternalSaveLoad;
 use crate::sector::{ExternalSectorEventType, InternalSectorEventType, Sector, SectorEvenReturnType, SectorEventType, SectorPosition};
 use crate::sector::SectorEvenReturnType::{Denied, Entered};
-use crate::time::{InternalTimeEventType, TimeEventType, TimeStackState};
+use crate::time::{ExternalTimeEventType, InternalTimeEventType, TimeClock, TimeEventReturnType, TimeEventType, TimeStackState};
+use crate::turn_phase::{TurnPhase, TurnPhaseHandler, TurnPipeline};
+use crate::market::Trade;
+use crate::contracts::{Contract, ContractBoard, OfPostContract};
+use crate::credits::{Credits, Transaction, TransactionKind, WalletId};
+use crate::wear::WearCatalog;
 
 pub struct MyLittleUniverse {
     time: TimeStackState,
     pub(crate) constructs: HashMap<String, Construct>,
     pub(crate) sectors: HashMap<SectorPosition, Sector>,
     universe_name: String,
+    turn_pipeline: TurnPipeline,
+    pub(crate) module_levels: ModuleLevelCatalog,
+    pub(crate) blueprints: BlueprintCatalog,
+    pub(crate) recipes: RecipeCatalog,
+    pub(crate) wear: WearCatalog,
+    // The trades of the last turn.
+    pub(crate) trades: Vec<Trade>,
+    pub(crate) credits: Credits,
+    pub(crate) contract_board: ContractBoard,
+    // The prices of the last turns, to show their trend.
+    pub(crate) price_history: HashMap<PriceScope, HashMap<Product, VecDeque<u32>>>,
 }
 
 
@@ -23,6 +47,18 @@ pub struct MyLittleUniverse {
 pub enum ExternalUniverseEventType {
     Move(OfMove),
     TransferCargo(OfTransferCargo),
+    GetProductionStats,
+    GetEconomyBalance(Option<SectorPosition>),
+    GetBalance(WalletId),
+    GetLedger(WalletId),
+    GrantCredits { payee: WalletId, amount: u64 },
+    TransferCredits { payer: WalletId, payee: WalletId, amount: u64 },
+    PostContract(OfPostContract),
+    AcceptContract { contract_id: u64, contractor: String },
+    GetContracts,
+    GetPrices(PriceScope),
+    AddSectorLink { from: SectorPosition, to: SectorPosition },
+    RemoveSectorLink { from: SectorPosition, to: SectorPosition },
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -30,11 +66,19 @@ pub struct OfTransferCargo {
     source_construct_name: String,
     target_construct_name: String,
     amount: Amount,
+    // Paid by the wallet of the source, that receives the cargo, to the wallet of the target.
+    #[serde(default)]
+    price_per_unit: Option<u64>,
 }
 
 impl OfTransferCargo {
     pub fn new(source_construct_name: String, target_construct_name: String, amount: Amount) -> Self {
-        OfTransferCargo { source_construct_name, target_construct_name, amount }
+        OfTransferCargo { source_construct_name, target_construct_name, amount, price_per_unit: None }
+    }
+
+    pub fn with_price_per_unit(mut self, price_per_unit: u64) -> Self {
+        self.price_per_unit = Some(price_per_unit);
+        self
     }
 }
 
@@ -58,8 +102,21 @@ pub enum MyLittleUniverseReturnValues {
     CouldNotFindSector(SectorPosition),
     MovedToSector(usize),
     CouldNotMoveToSector(String),
+    // The turn the construct arrives in the target sector.
+    InTransit(u64),
     Denied(String),
     CargoTransfered(u32),
+    ProductionStats(ProductionStats),
+    EconomyBalance(EconomyBalance),
+    Balance(u64),
+    Ledger(Vec<Transaction>),
+    CreditsTransferred(u64),
+    ContractPosted(u64),
+    ContractAccepted,
+    Contracts(Vec<Contract>),
+    Prices(Vec<ProductPrice>),
+    SectorLinkAdded,
+    SectorLinkRemoved,
 }
 
 impl MyLittleUniverse {
@@ -69,11 +126,50 @@ impl MyLittleUniverse {
             universe_name,
             constructs,
             sectors,
+            turn_pipeline: TurnPipeline::new(),
+            module_levels: ModuleLevelCatalog::default(),
+            blueprints: BlueprintCatalog::default(),
+            recipes: RecipeCatalog::default(),
+            wear: WearCatalog::default(),
+            trades: Vec::new(),
+            credits: Credits::default(),
+            contract_board: ContractBoard::default(),
+            price_history: HashMap::new(),
         }
     }
     pub fn time(&self) -> &TimeStackState {
         &self.time
     }
+    pub fn set_clock(&mut self, clock: TimeClock) {
+        self.time.set_clock(clock);
+    }
+    pub fn set_module_levels(&mut self, module_levels: ModuleLevelCatalog) {
+        self.module_levels = module_levels;
+    }
+    pub fn set_recipes(&mut self, recipes: RecipeCatalog) {
+        self.recipes = recipes;
+    }
+    // Saved modules keep the recipes they were built with, after loading they should follow the catalog.
+    pub fn refresh_recipes(&mut self) {
+        for construct in self.constructs.values_mut() {
+            construct.refresh_recipes(&self.recipes);
+        }
+    }
+    pub fn set_blueprints(&mut self, blueprints: BlueprintCatalog) {
+        self.blueprints = blueprints;
+    }
+    pub fn set_wear(&mut self, wear: WearCatalog) {
+        self.wear = wear;
+    }
+    pub fn set_credits(&mut self, credits: Credits) {
+        self.credits = credits;
+    }
+    pub fn set_contract_board(&mut self, contract_board: ContractBoard) {
+        self.contract_board = contract_board;
+    }
+    pub fn last_trades(&self) -> &Vec<Trade> {
+        &self.trades
+    }
     pub fn constructs(&self) -> &HashMap<String, Construct> {
         &self.constructs
     }
@@ -81,8 +177,22 @@ impl MyLittleUniverse {
         &self.universe_name
     }
 
-    pub fn handle_event(&mut self, event: ExternalCommands) -> ExternalCommandReturnValues {
+    pub fn handle_event(&mut self, mut event: ExternalCommands) -> ExternalCommandReturnValues {
+        if let Err(message) = self.resolve_module_products(&mut event) {
+            return match event {
+                ExternalCommands::Time(_) => ExternalCommandReturnValues::Time(TimeEventReturnType::Denied(message)),
+                ExternalCommands::Construct(..) => ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(message)),
+                _ => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(message)),
+            };
+        }
+
         match event {
+            ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(mut breakpoint)) => {
+                // A breakpoint that is already met when added, first fires after it has been unmet.
+                breakpoint.update_condition_met(self.is_condition_met(breakpoint.condition()));
+                let return_type = self.time.push_event(&TimeEventType::External(ExternalTimeEventType::AddBreakpoint(breakpoint)));
+                ExternalCommandReturnValues::Time(return_type)
+            }
             ExternalCommands::Time(time_event) => {
                 let return_type = self.time.push_event(&TimeEventType::External(time_event));
                 ExternalCommandReturnValues::Time(return_type)
@@ -95,17 +205,22 @@ impl MyLittleUniverse {
                                 match construct.position.position() {
                                     ConstructPositionStatus::InSector(sector_name) =>
                                         return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(ConstructPositionEventReturnType::Denied(format!("Cannot undock because is not docked. Is in sector {:?}", sector_name)))),
-                                    ConstructPositionStatus::IsDocked(the_docked_at_name) => the_docked_at_name.clone()
+                                    ConstructPositionStatus::IsDocked(the_docked_at_name) => the_docked_at_name.clone(),
+                                    ConstructPositionStatus::InTransit { to, .. } =>
+                                        return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(ConstructPositionEventReturnType::Denied(format!("Cannot undock because is not docked. Is in transit to {:?}", to)))),
                                 }
                             }
                             None => { return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotFindConstruct(construct_name)); }
                         };
+                        let sector_position = match self.get_sector_position(construct_name.clone()) {
+                            Some(sector_position) => sector_position.clone(),
+                            None => return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(ConstructPositionEventReturnType::Denied(format!("Cannot undock because {} is in transit.", docked_at_name))))
+                        };
 
                         self.constructs.get_mut(&docked_at_name)
                             .expect("The construct does not exists in universe list of constructs")
                             .position.handle_event(&ConstructPositionEventType::Internal(InternalConstructPositionEventType::Undocked(construct_name.clone())));
 
-                        let sector_position = self.get_sector_position(construct_name.clone()).clone();
                         let construct = self.constructs.get_mut(&construct_name).unwrap();
                         let return_type = construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::ConstructPosition(InternalConstructPositionEventType::Undock(sector_position.clone()))));
 
@@ -116,6 +231,65 @@ impl MyLittleUniverse {
                     ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock(target_construct_name)) => {
                         return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(self.handle_docking_request(construct_name, target_construct_name)));
                     }
+                    ExternalConstructEventType::UpgradeModule { module_name } => {
+                        let construct = match self.constructs.get_mut(&construct_name) {
+                            Some(construct) => construct,
+                            None => return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotFindConstruct(construct_name))
+                        };
+                        let next_level = match construct.production_module(&module_name) {
+                            Some(production_module) => production_module.level() + 1,
+                            None => return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(format!("No production module named {} installed.", module_name)))
+                        };
+                        let module_level = match self.module_levels.get(next_level) {
+                            Some(module_level) => module_level.clone(),
+                            None => return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(format!("Module {} is already at the highest level.", module_name)))
+                        };
+                        ExternalCommandReturnValues::Construct(construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::UpgradeModule { module_name, module_level })))
+                    }
+                    ExternalConstructEventType::AddProductionOrder { module_name, recipe_id, target } => {
+                        let recipe = match self.recipes.get(&recipe_id) {
+                            Some(recipe) => recipe.clone(),
+                            None => return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(format!("No recipe named {}.", recipe_id)))
+                        };
+                        if let OrderTarget::UntilStock(amount) = &target {
+                            if !recipe.output().iter().any(|output| output.product().eq(amount.product())) {
+                                return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(format!("Recipe {} does not produce {}.", recipe_id, amount.product())));
+                            }
+                        }
+                        match self.constructs.get_mut(&construct_name) {
+                            Some(construct) => ExternalCommandReturnValues::Construct(construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::AddProductionOrder { module_name, order: ProductionOrder::new(recipe, target) }))),
+                            None => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotFindConstruct(construct_name))
+                        }
+                    }
+                    ExternalConstructEventType::BuildConstruct { module_name, blueprint_id, construct_name: new_construct_name } => {
+                        let blueprint = match self.blueprints.get(&blueprint_id) {
+                            Some(blueprint) => blueprint.clone(),
+                            None => return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(format!("No blueprint named {}.", blueprint_id)))
+                        };
+                        if self.is_construct_name_taken(&new_construct_name) {
+                            return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(format!("A construct named {} already exists.", new_construct_name)));
+                        }
+                        match self.constructs.get_mut(&construct_name) {
+                            Some(construct) => ExternalCommandReturnValues::Construct(construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::BuildConstruct { module_name, blueprint, construct_name: new_construct_name }))),
+                            None => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotFindConstruct(construct_name))
+                        }
+                    }
+                    ExternalConstructEventType::InstallModule { module_name, installed_name, price: Some(price) } => {
+                        if !self.constructs.contains_key(&construct_name) {
+                            return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotFindConstruct(construct_name));
+                        }
+                        let payer = self.wallet_of(&construct_name);
+                        if !self.credits.can_pay(&payer, price.amount()) {
+                            return ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied(format!("{:?} has {} credits and cannot pay {}.", payer, self.credits.balance(&payer), price.amount())));
+                        }
+                        let return_type = self.constructs.get_mut(&construct_name).unwrap()
+                            .push_event(&ConstructEventType::External(ExternalConstructEventType::InstallModule { module_name, installed_name, price: None }));
+                        if return_type == ConstructEvenReturnType::ModuleInstalled {
+                            self.credits.transfer(self.time.turn(), &payer, price.payee(), price.amount(), TransactionKind::ModuleInstall)
+                                .expect("The funds were checked before installing.");
+                        }
+                        ExternalCommandReturnValues::Construct(return_type)
+                    }
                     _ => {
                         return match self.constructs.get_mut(&construct_name) {
                             Some(construct) => {
@@ -168,6 +342,44 @@ impl MyLittleUniverse {
             ExternalCommands::Universe(event) => {
                 match event {
                     ExternalUniverseEventType::Move(of_move_to_sector) => ExternalCommandReturnValues::Universe(self.move_to_sector(of_move_to_sector)),
+                    ExternalUniverseEventType::GetProductionStats => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ProductionStats(self.production_stats())),
+                    ExternalUniverseEventType::GetEconomyBalance(sector_position) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::EconomyBalance(self.economy_balance(sector_position))),
+                    ExternalUniverseEventType::GetBalance(wallet_id) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Balance(self.credits.balance(&wallet_id))),
+                    ExternalUniverseEventType::GetLedger(wallet_id) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Ledger(self.credits.transactions_of(&wallet_id))),
+                    ExternalUniverseEventType::GrantCredits { payee, amount } => {
+                        self.credits.grant(self.time.turn(), &payee, amount);
+                        ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CreditsTransferred(amount))
+                    }
+                    ExternalUniverseEventType::TransferCredits { payer, payee, amount } => {
+                        match self.credits.transfer(self.time.turn(), &payer, &payee, amount, TransactionKind::Transfer) {
+                            Ok(()) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CreditsTransferred(amount)),
+                            Err(message) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(message))
+                        }
+                    }
+                    ExternalUniverseEventType::PostContract(of_post_contract) => {
+                        match self.post_contract(of_post_contract) {
+                            Ok(contract_id) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ContractPosted(contract_id)),
+                            Err(message) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(message))
+                        }
+                    }
+                    ExternalUniverseEventType::AcceptContract { contract_id, contractor } => {
+                        match self.accept_contract(contract_id, contractor) {
+                            Ok(()) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ContractAccepted),
+                            Err(message) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(message))
+                        }
+                    }
+                    ExternalUniverseEventType::GetContracts => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Contracts(self.contract_board.contracts().clone())),
+                    ExternalUniverseEventType::AddSectorLink { from, to } => ExternalCommandReturnValues::Universe(self.add_sector_link(from, to)),
+                    ExternalUniverseEventType::RemoveSectorLink { from, to } => ExternalCommandReturnValues::Universe(self.remove_sector_link(from, to)),
+                    ExternalUniverseEventType::GetPrices(price_scope) => {
+                        match self.prices(&price_scope) {
+                            Ok(prices) => {
+                                self.watch_prices(price_scope);
+                                ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Prices(prices))
+                            }
+                            Err(message) => ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(message))
+                        }
+                    }
                     ExternalUniverseEventType::TransferCargo(transfer_cargo) => {
                         if transfer_cargo.source_construct_name.eq(&transfer_cargo.target_construct_name) {
                             return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("Cannot transfer because source {} and target {} construct is the same", transfer_cargo.source_construct_name, transfer_cargo.target_construct_name)));
@@ -183,6 +395,13 @@ impl MyLittleUniverse {
                             Some(target_construct) => target_construct
                         };
 
+                        // Constructs docked at a construct in transit travel along, and cannot transfer either.
+                        for construct_name in [&transfer_cargo.source_construct_name, &transfer_cargo.target_construct_name] {
+                            if self.get_sector_position(construct_name.clone()).is_none() {
+                                return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("Cannot transfer, because {} is in transit.", construct_name)));
+                            }
+                        }
+
                         match source_construct.position.position() {
                             ConstructPositionStatus::InSector(source_position) => {
                                 match target_construct.position.position() {
@@ -194,6 +413,7 @@ impl MyLittleUniverse {
                                             return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("Cannot transfer, because {} is docked at {:?} and {} is docked at {:?}; One need to be docked at the other.", transfer_cargo.source_construct_name, source_position, transfer_cargo.target_construct_name, target_docked_at_name)));
                                         } else {}
                                     }
+                                    ConstructPositionStatus::InTransit { .. } => panic!("Constructs in transit were denied before.")
                                 }
                             }
                             ConstructPositionStatus::IsDocked(source_docked_at_name) => {
@@ -201,6 +421,20 @@ impl MyLittleUniverse {
                                     return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("Cannot transfer, because {} is docked at {:?} and that is not {}.", transfer_cargo.source_construct_name, source_docked_at_name, transfer_cargo.target_construct_name)));
                                 } else {}
                             }
+                            ConstructPositionStatus::InTransit { .. } => panic!("Constructs in transit were denied before.")
+                        }
+
+                        // The buyer needs the credits for the whole request, but only pays for what is moved.
+                        let buyer = self.wallet_of(&transfer_cargo.source_construct_name);
+                        let seller = self.wallet_of(&transfer_cargo.target_construct_name);
+                        if let Some(price_per_unit) = transfer_cargo.price_per_unit {
+                            let total = match price_per_unit.checked_mul(u64::from(transfer_cargo.amount.amount())) {
+                                Some(total) => total,
+                                None => return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("A price of {} for {} units is too high.", price_per_unit, transfer_cargo.amount.amount())))
+                            };
+                            if !self.credits.can_pay(&buyer, total) {
+                                return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("{:?} has {} credits and cannot pay {}.", buyer, self.credits.balance(&buyer), total)));
+                            }
                         }
 
                         let amount = match self.constructs.get_mut(&transfer_cargo.target_construct_name).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(transfer_cargo.amount.clone()))) {
@@ -208,24 +442,75 @@ impl MyLittleUniverse {
                             return_value => return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("Could not unload {:?} from {}, got this message {:?}", transfer_cargo.amount, transfer_cargo.target_construct_name, return_value)))
                         };
 
-                        let amount = match self.constructs.get_mut(&transfer_cargo.source_construct_name).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(transfer_cargo.amount.product().clone(), amount)))) {
-                            ConstructEvenReturnType::RequestLoadProcessed(amount) => amount,
+                        let loaded = match self.constructs.get_mut(&transfer_cargo.source_construct_name).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(transfer_cargo.amount.product().clone(), amount)))) {
+                            ConstructEvenReturnType::RequestLoadProcessed(loaded) => loaded,
                             return_value => return ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied(format!("Could not load {:?} from {}, got this message {:?}", transfer_cargo.amount, transfer_cargo.source_construct_name, return_value)))
                         };
+                        // What did not fit goes back, it came from there so there is room for it.
+                        if loaded < amount {
+                            self.constructs.get_mut(&transfer_cargo.target_construct_name).unwrap()
+                                .load_request(&Amount::new(transfer_cargo.amount.product().clone(), amount - loaded));
+                        }
+
+                        if let Some(price_per_unit) = transfer_cargo.price_per_unit {
+                            self.credits.transfer(self.time.turn(), &buyer, &seller, price_per_unit * u64::from(loaded), TransactionKind::Cargo)
+                                .expect("The funds were checked before the transfer.");
+                        }
+                        // Only the cargo that arrived counts for the contracts.
+                        self.deliver_to_contracts(&transfer_cargo.target_construct_name, &transfer_cargo.source_construct_name, transfer_cargo.amount.product(), loaded);
 
-                        ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CargoTransfered(amount))
+                        ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CargoTransfered(loaded))
                     }
                 }
             }
         }
     }
 
-    fn get_sector_position(&self, construct_name: String) -> &ConstructPositionSector {
+    // Module:<name> in a command stands for the module the recipes build under that name.
+    fn resolve_module_products(&self, event: &mut ExternalCommands) -> Result<(), String> {
+        let product = match event {
+            ExternalCommands::Time(ExternalTimeEventType::AddBreakpoint(breakpoint)) => breakpoint.product_mut(),
+            ExternalCommands::Construct(_, ExternalConstructEventType::AddProductionOrder { target: OrderTarget::UntilStock(amount), .. }) => Some(amount.product_mut()),
+            ExternalCommands::Construct(_, ExternalConstructEventType::PostMarketOrder { product, .. }) => Some(product),
+            ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(transfer_cargo)) => Some(transfer_cargo.amount.product_mut()),
+            ExternalCommands::Universe(ExternalUniverseEventType::PostContract(post_contract)) => Some(post_contract.product_mut()),
+            _ => None
+        };
+        match product {
+            Some(product) => match product.named_module() {
+                Some(module_name) => {
+                    *product = self.recipes.module_product(module_name)
+                        .ok_or(format!("No recipe builds a module named {}.", module_name))?
+                        .clone();
+                    Ok(())
+                }
+                None => Ok(())
+            },
+            None => Ok(())
+        }
+    }
+
+    // All production modules in the universe added together.
+    pub fn production_stats(&self) -> ProductionStats {
+        let mut production_stats = ProductionStats::default();
+        for construct in self.constructs.values() {
+            for module in construct.modules() {
+                if let ConstructModuleType::Production(production_module) = module {
+                    production_stats.add(production_module.stats());
+                }
+            }
+        }
+        production_stats
+    }
+
+    // None while the construct, or the construct it is docked at, is in transit.
+    pub(crate) fn get_sector_position(&self, construct_name: String) -> Option<&ConstructPositionSector> {
         match self.constructs.get(construct_name.as_str()).expect("Looked up a construct_name that does not exist anymore").position.position() {
             ConstructPositionStatus::IsDocked(docker_construct_name) => {
                 self.get_sector_position(docker_construct_name.clone())
             }
-            ConstructPositionStatus::InSector(sector_position) => sector_position
+            ConstructPositionStatus::InSector(sector_position) => Some(sector_position),
+            ConstructPositionStatus::InTransit { .. } => None
         }
     }
 
@@ -235,19 +520,27 @@ impl MyLittleUniverse {
         }
 
         //Handling source target first.
-        match self.constructs.get(&of_move_to_sector.construct_name) {
+        let (source_sector_position, speed) = match self.constructs.get(&of_move_to_sector.construct_name) {
             Some(construct) => {
                 let position = construct.position().position();
                 match position {
                     ConstructPositionStatus::IsDocked(construct_name) => {
                         return MyLittleUniverseReturnValues::CouldNotMoveToSector(format!("Is docked at {}", construct_name));
                     }
+                    ConstructPositionStatus::InTransit { to, .. } => {
+                        return MyLittleUniverseReturnValues::CouldNotMoveToSector(format!("Is in transit to {:?}", to));
+                    }
                     ConstructPositionStatus::InSector(source_sector_position) => {
                         if source_sector_position.sector_position().eq(&of_move_to_sector.sector_position)
                             && of_move_to_sector.group_address.is_some()
                             && source_sector_position.group_address().eq(&of_move_to_sector.group_address.unwrap()) {
                             return MyLittleUniverseReturnValues::CouldNotMoveToSector(format!("Construct {:?} is already in target position {:?}", of_move_to_sector.construct_name, of_move_to_sector));
                         }
+                        // Other sectors can only be reached through a jump gate.
+                        if source_sector_position.sector_position().ne(&of_move_to_sector.sector_position)
+                            && !self.sectors.get(source_sector_position.sector_position()).is_some_and(|sector| sector.is_linked_to(&of_move_to_sector.sector_position)) {
+                            return MyLittleUniverseReturnValues::CouldNotMoveToSector(format!("No jump gate from {:?} to {:?}", source_sector_position.sector_position(), of_move_to_sector.sector_position));
+                        }
 
                         match self.sectors.get_mut(source_sector_position.sector_position()) {
                             Some(source_sector) => {
@@ -265,44 +558,96 @@ impl MyLittleUniverse {
                                 return MyLittleUniverseReturnValues::CouldNotMoveToSector(format!("Source sector does not exist {:?}", of_move_to_sector.sector_position));
                             }
                         }
+                        (source_sector_position.sector_position().clone(), construct.speed())
                     }
                 }
             }
             None => {
                 return MyLittleUniverseReturnValues::CouldNotMoveToSector(format!("Construct does not exist {}", of_move_to_sector.construct_name));
             }
+        };
+
+        //Moving within the sector is instant, other sectors take turns depending on the distance and speed.
+        if source_sector_position.eq(&of_move_to_sector.sector_position) {
+            return MyLittleUniverseReturnValues::MovedToSector(self.enter_sector(&of_move_to_sector.construct_name, &of_move_to_sector.sector_position, of_move_to_sector.group_address));
         }
+        let travel_turns = source_sector_position.distance_to(&of_move_to_sector.sector_position).div_ceil(speed);
+        let arrival_turn = self.time.turn() + travel_turns as u64;
+        self.constructs.get_mut(&of_move_to_sector.construct_name).unwrap()
+            .push_event(&ConstructEventType::External(ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Depart {
+                from: source_sector_position,
+                to: of_move_to_sector.sector_position,
+                group_address: of_move_to_sector.group_address,
+                arrival_turn,
+            })));
+
+        MyLittleUniverseReturnValues::InTransit(arrival_turn)
+    }
 
-        //Then handle target sector
-        let group_id = match self.sectors.get_mut(&of_move_to_sector.sector_position) {
+    fn enter_sector(&mut self, construct_name: &String, sector_position: &SectorPosition, group_address: Option<usize>) -> usize {
+        let group_id = match self.sectors.get_mut(sector_position) {
             Some(target_sector) => {
-                if let Entered(group_id) = target_sector.push_event(&SectorEventType::Internal(InternalSectorEventType::Enter(of_move_to_sector.construct_name.clone(), of_move_to_sector.group_address))) {
+                if let Entered(group_id) = target_sector.push_event(&SectorEventType::Internal(InternalSectorEventType::Enter(construct_name.clone(), group_address))) {
                     group_id
                 } else {
-                    panic!("Constructs are in bad state; It is removed from one sector but not added to the new one. Construct_name: {}; Reason: Target did not accept construct entering.", of_move_to_sector.construct_name);
+                    panic!("Constructs are in bad state; It is removed from one sector but not added to the new one. Construct_name: {}; Reason: Target did not accept construct entering.", construct_name);
                 }
             }
             None => {
-                panic!("Constructs are in bad state; It is removed from one sector but not added to the new one. Construct_name: {}; Reason: Target were gone when needed.", of_move_to_sector.construct_name);
+                panic!("Constructs are in bad state; It is removed from one sector but not added to the new one. Construct_name: {}; Reason: Target were gone when needed.", construct_name);
             }
         };
 
-        //Handling construct
-        self.constructs.get_mut(&of_move_to_sector.construct_name).unwrap()
+        self.constructs.get_mut(construct_name).unwrap()
             .push_event(&ConstructEventType::External(ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::EnterSector(
-                ConstructPositionSector::new(of_move_to_sector.sector_position.clone(), group_id)
+                ConstructPositionSector::new(sector_position.clone(), group_id)
             ))));
+        group_id
+    }
 
-
-        MyLittleUniverseReturnValues::MovedToSector(group_id)
+    // Movement phase, constructs that reach their arrival turn enter the target sector.
+    pub(crate) fn arrive_constructs(&mut self, current_turn: &u64) {
+        for construct_name in self.construct_names_in_turn_order() {
+            if let ConstructPositionStatus::InTransit { to, group_address, arrival_turn, .. } = self.constructs.get(&construct_name).unwrap().position().position().clone() {
+                if arrival_turn <= *current_turn {
+                    self.enter_sector(&construct_name, &to, group_address);
+                }
+            }
+        }
     }
 
-    pub fn request_execute_turn(&mut self) {
+    pub fn request_execute_turn(&mut self) -> bool {
         if self.time.request_execute_turn() {
-            for construct in self.constructs.values_mut() {
-                construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::ExecuteTurn(self.time.turn())));
+            let current_turn = self.time.turn();
+            for phase in TurnPhase::IN_ORDER {
+                for handler in self.turn_pipeline.handlers(&phase) {
+                    handler(self, &current_turn);
+                }
             }
             self.time.push_event(&TimeEventType::Internal(InternalTimeEventType::ReadyForNextTurn));
+            return true;
+        }
+        false
+    }
+
+    pub fn register_turn_phase_handler(&mut self, phase: TurnPhase, handler: TurnPhaseHandler) {
+        self.turn_pipeline.register(phase, handler);
+    }
+
+    // Constructs are handled in name order, so a turn does not depend on the HashMap iteration order.
+    pub fn construct_names_in_turn_order(&self) -> Vec<String> {
+        let mut construct_names: Vec<String> = self.constructs.keys().cloned().collect();
+        construct_names.sort();
+        construct_names
+    }
+
+    pub(crate) fn evaluate_breakpoints(&mut self, _current_turn: &u64) {
+        let conditions_met = self.time.breakpoints().iter()
+            .map(|breakpoint| self.is_condition_met(breakpoint.condition()))
+            .collect();
+        let fired_breakpoints = self.time.update_breakpoints(conditions_met);
+        if !fired_breakpoints.is_empty() {
+            self.time.push_event(&TimeEventType::Internal(InternalTimeEventType::BreakpointsFired(fired_breakpoints)));
         }
     }
 }
@@ -317,13 +662,15 @@ mod tests_int {
     use crate::construct::construct::{Construct, ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
     use crate::construct::construct_position::{ConstructPositionEventReturnType, ConstructPositionSector, ConstructPositionStatus, ExternalConstructPositionEventType};
     use crate::construct::construct_position::ConstructPositionStatus::{IsDocked, InSector};
-    use crate::construct::production_module::ProductionModule;
+    use crate::construct::production_module::{IdleReason, OrderTarget, ProductionModule};
     use crate::construct_module::ConstructModuleType::Production;
+    use crate::module_levels::read_module_level_catalog;
     use crate::my_little_universe::{ExternalUniverseEventType, MyLittleUniverse, MyLittleUniverseReturnValues, OfMove, OfTransferCargo};
     use crate::products::Product;
     use crate::sector::{ExternalSectorEventType, SectorEvenReturnType, SectorPosition};
     use crate::time::{ExternalTimeEventType, TimeEventReturnType, TimeStackState};
-    use crate::universe_generator::generate_simple_universe;
+    use crate::recipes::read_recipe_catalog;
+    use crate::universe_generator::{add_distant_sector, generate_simple_universe};
 
     #[test]
     fn it_works() {
@@ -332,8 +679,8 @@ mod tests_int {
         let mut construct = Construct::new("The base".to_string(), 500, sector_position);
         let ore_production = ProductionModule::new(
             "PowerToOre".to_string(),
-            vec![Amount::new(Product::PowerCells, 1)],
-            vec![Amount::new(Product::Ores, 2)],
+            vec![Amount::new(Product::ware("PowerCells"), 1)],
+            vec![Amount::new(Product::ware("Ores"), 2)],
             1,
             0,
         );
@@ -348,11 +695,11 @@ mod tests_int {
         //testing
         assert_eq!(
             ConstructEvenReturnType::RequestLoadProcessed(200),
-            universe.constructs.get_mut(construct_name.as_str().clone()).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::PowerCells, 200))))
+            universe.constructs.get_mut(construct_name.as_str()).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 200))))
         );
         assert_eq!(
             ConstructEvenReturnType::RequestUnloadProcessed(0),
-            universe.constructs.get_mut(construct_name.as_str().clone()).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(Amount::new(Product::Ores, 2))))
+            universe.constructs.get_mut(construct_name.as_str()).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(Amount::new(Product::ware("Ores"), 2))))
         );
 
         assert_eq!(
@@ -371,7 +718,7 @@ mod tests_int {
 
         assert_eq!(
             ConstructEvenReturnType::RequestUnloadProcessed(2),
-            universe.constructs.get_mut(construct_name.as_str().clone()).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(Amount::new(Product::Ores, 2))))
+            universe.constructs.get_mut(construct_name.as_str()).unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(Amount::new(Product::ware("Ores"), 2))))
         );
 
         assert_eq!(
@@ -380,9 +727,104 @@ mod tests_int {
         );
     }
 
+    #[test]
+    fn upgrade_module() {
+        let mut universe = generate_simple_universe("the_universe".to_string(), &read_recipe_catalog("default"));
+        universe.set_module_levels(read_module_level_catalog("default"));
+        let upgrade = ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::UpgradeModule { module_name: "PowerToOre".to_string() });
+
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 9))));
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 10))));
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("Upgrading PowerToOre to level 2 needs 10 Metals.".to_string())),
+            universe.handle_event(upgrade.clone())
+        );
+
+        universe.constructs.get_mut("The_base_1").unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 1))));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ModuleUpgraded(2)), universe.handle_event(upgrade.clone()));
+
+        let base = universe.constructs.get("The_base_1").unwrap();
+        assert_eq!(None, base.current_storage().get(&Product::ware("Metals")));
+        assert_eq!(None, base.current_storage().get(&Product::ware("PowerCells")));
+        assert_eq!(vec![Amount::new(Product::ware("Ores"), 4)], base.production_module("PowerToOre").unwrap().leveled_output());
+
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 25))));
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 25))));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ModuleUpgraded(3)), universe.handle_event(upgrade.clone()));
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("Module PowerToOre is already at the highest level.".to_string())),
+            universe.handle_event(upgrade)
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("No production module named Nope installed.".to_string())),
+            universe.handle_event(ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::UpgradeModule { module_name: "Nope".to_string() }))
+        );
+    }
+
+    #[test]
+    fn production_stats_of_all_modules() {
+        let mut universe = generate_simple_universe("the_universe".to_string(), &read_recipe_catalog("default"));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        assert!(universe.request_execute_turn());
+        assert!(universe.request_execute_turn());
+
+        if let ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::ProductionStats(stats)) = universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GetProductionStats)) {
+            assert_eq!(0, stats.completed_cycles());
+            assert_eq!(2, stats.idle_turns_for(&IdleReason::MissingInput(Product::ware("PowerCells"))));
+            assert_eq!(2, stats.idle_turns_for(&IdleReason::MissingInput(Product::ware("Ores"))));
+        } else {
+            panic!("Expected the production stats");
+        }
+    }
+
+    #[test]
+    fn production_orders() {
+        let recipe_catalog = read_recipe_catalog("default");
+        let mut universe = generate_simple_universe("the_universe".to_string(), &recipe_catalog);
+        universe.set_recipes(recipe_catalog);
+        let add_order = |recipe_id: &str, target: OrderTarget| ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::AddProductionOrder { module_name: "PowerToOre".to_string(), recipe_id: recipe_id.to_string(), target });
+
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("No recipe named Unknown.".to_string())), universe.handle_event(add_order("Unknown", OrderTarget::Batches(1))));
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("Recipe OreToPower does not produce Ores.".to_string())),
+            universe.handle_event(add_order("OreToPower", OrderTarget::UntilStock(Amount::new(Product::ware("Ores"), 4))))
+        );
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ProductionOrderAdded(0)), universe.handle_event(add_order("OreToPower", OrderTarget::Batches(1))));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ProductionOrderAdded(1)), universe.handle_event(add_order("PowerToOre", OrderTarget::UntilStock(Amount::new(Product::ware("Ores"), 4)))));
+        assert_eq!(ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ProductionOrderAdded(2)), universe.handle_event(add_order("PowerToOre", OrderTarget::Batches(9))));
+        assert!(matches!(
+            universe.handle_event(ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::RemoveProductionOrder { module_name: "PowerToOre".to_string(), index: 2 })),
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ProductionOrderRemoved(_))
+        ));
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("Module PowerToOre has no order 2, it has 2 orders.".to_string())),
+            universe.handle_event(ExternalCommands::Construct("The_base_1".to_string(), ExternalConstructEventType::RemoveProductionOrder { module_name: "PowerToOre".to_string(), index: 2 }))
+        );
+
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 10))));
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Ores"), 1))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        for _ in 0..5 {
+            assert!(universe.request_execute_turn());
+        }
+
+        // One batch of metals, then ores until there are four, then the module stops.
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert_eq!(Some(&2), base.current_storage().get(&Product::ware("Metals")));
+        assert_eq!(Some(&4), base.current_storage().get(&Product::ware("Ores")));
+        assert_eq!(Some(&8), base.current_storage().get(&Product::ware("PowerCells")));
+        let production_module = base.production_module("PowerToOre").unwrap();
+        assert!(production_module.orders().is_empty());
+        assert_eq!(&Some("PowerToOre".to_string()), production_module.recipe_id());
+        assert!(!production_module.enabled());
+    }
+
     #[test]
     fn move_sectors() {
-        let mut universe = generate_simple_universe("the_universe".to_string());
+        let mut universe = generate_simple_universe("the_universe".to_string(), &read_recipe_catalog("default"));
 
         if let ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructState(construct)) = universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::GetConstructState { include_stack: false })) {
             assert_eq!(&InSector(ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0)), construct.position.position());
@@ -400,15 +842,39 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), SectorPosition::new(3, 3, 3), None)))),
         );
 
+        // The next sector is one turn away, in transit the transport cannot move, dock or transfer.
         assert_eq!(
-            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::MovedToSector(1)),
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::InTransit(1)),
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), SectorPosition::new(2, 2, 2), None)))),
         );
+        let in_transit = ConstructPositionStatus::InTransit { from: SectorPosition::new(1, 1, 1), to: SectorPosition::new(2, 2, 2), group_address: None, arrival_turn: 1 };
+        verify_construct(&mut universe, &in_transit, "transport");
+        verify_sector_position(&mut universe, in_transit, "transport");
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotMoveToSector("Is in transit to SectorPosition { x: 2, y: 2, z: 2 }".to_string())),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), SectorPosition::new(1, 1, 1), None)))),
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(ConstructPositionEventReturnType::Denied("Construct transport is in transit to SectorPosition { x: 2, y: 2, z: 2 } and cannot dock.".to_string()))),
+            universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_2".to_string())))),
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Cannot transfer, because transport is in transit.".to_string())),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new("The_base_2".to_string(), "transport".to_string(), Amount::new(Product::ware("Ores"), 1))))),
+        );
 
+        finish_travel(&mut universe);
+        verify_construct(&mut universe, &InSector(ConstructPositionSector::new(SectorPosition::new(2, 2, 2), 1)), "transport");
+
+        // A faster construct covers more sectors per turn.
+        assert_eq!(3, SectorPosition::new(1, 1, 1).distance_to(&SectorPosition::new(4, 2, 1)));
+        let transport = universe.constructs.remove("transport").unwrap().with_speed(3);
+        universe.constructs.insert("transport".to_string(), transport);
         assert_eq!(
-            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::MovedToSector(1)),
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::InTransit(2)),
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), SectorPosition::new(1, 1, 1), None)))),
         );
+        finish_travel(&mut universe);
 
         assert_eq!(
             ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotMoveToSector("Construct \"transport\" is already in target position OfMove { construct_name: \"transport\", sector_position: SectorPosition { x: 1, y: 1, z: 1 }, group_address: Some(1) }".to_string())),
@@ -423,7 +889,7 @@ mod tests_int {
 
     #[test]
     fn docking() {
-        let mut universe = generate_simple_universe("the_universe".to_string());
+        let mut universe = generate_simple_universe("the_universe".to_string(), &read_recipe_catalog("default"));
 
         verify_all_constructs_position(&mut universe,
                                        InSector(ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0)),
@@ -454,9 +920,10 @@ mod tests_int {
         );
 
         assert_eq!(
-            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::MovedToSector(1)),
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::InTransit(1)),
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), SectorPosition::new(2, 2, 2), None)))),
         );
+        finish_travel(&mut universe);
 
         verify_all_constructs_position(&mut universe,
                                        InSector(ConstructPositionSector::new(SectorPosition::new(2, 2, 2), 1)),
@@ -541,10 +1008,16 @@ mod tests_int {
                                        InSector(ConstructPositionSector::new(SectorPosition::new(2, 2, 2), 0)),
         );
 
+        // The transport travels along docked at the base, and cannot undock on the way.
         assert_eq!(
-            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::MovedToSector(0)),
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::InTransit(2)),
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("The_base_2".to_string(), SectorPosition::new(1, 1, 1), Some(0))))),
         );
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(ConstructPositionEventReturnType::Denied("Cannot undock because The_base_2 is in transit.".to_string()))),
+            universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Undock))),
+        );
+        finish_travel(&mut universe);
 
         verify_all_constructs_position(&mut universe,
                                        IsDocked("The_base_2".to_string()),
@@ -577,11 +1050,11 @@ mod tests_int {
 
     #[test]
     fn transfering() {
-        let mut universe = generate_simple_universe("the_universe".to_string());
+        let mut universe = generate_simple_universe("the_universe".to_string(), &read_recipe_catalog("default"));
 
         assert_eq!(
             ConstructEvenReturnType::RequestLoadProcessed(200),
-            universe.constructs.get_mut("The_base_1").unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::PowerCells, 200))))
+            universe.constructs.get_mut("The_base_1").unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 200))))
         );
 
         assert_eq!(
@@ -589,7 +1062,7 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "transport".to_string(),
                 "transport".to_string(),
-                Amount::new(Product::Metals, 100),
+                Amount::new(Product::ware("Metals"), 100),
             )))),
         );
 
@@ -598,7 +1071,7 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "transport".to_string(),
                 "The_base_1".to_string(),
-                Amount::new(Product::Metals, 100),
+                Amount::new(Product::ware("Metals"), 100),
             )))),
         );
 
@@ -612,7 +1085,7 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "transport".to_string(),
                 "The_base_1".to_string(),
-                Amount::new(Product::Metals, 100),
+                Amount::new(Product::ware("Metals"), 100),
             )))),
         );
 
@@ -621,7 +1094,7 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "transport".to_string(),
                 "The_base_1".to_string(),
-                Amount::new(Product::PowerCells, 50),
+                Amount::new(Product::ware("PowerCells"), 50),
             )))),
         );
 
@@ -630,7 +1103,7 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "transport".to_string(),
                 "The_base_1".to_string(),
-                Amount::new(Product::PowerCells, 200),
+                Amount::new(Product::ware("PowerCells"), 200),
             )))),
         );
 
@@ -639,7 +1112,7 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "transport".to_string(),
                 "The_base_1".to_string(),
-                Amount::new(Product::PowerCells, 200),
+                Amount::new(Product::ware("PowerCells"), 200),
             )))),
         );
 
@@ -648,7 +1121,7 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "The_base_1".to_string(),
                 "transport".to_string(),
-                Amount::new(Product::PowerCells, 300),
+                Amount::new(Product::ware("PowerCells"), 300),
             )))),
         );
 
@@ -657,11 +1130,87 @@ mod tests_int {
             universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new(
                 "The_base_1".to_string(),
                 "transport".to_string(),
-                Amount::new(Product::PowerCells, 300),
+                Amount::new(Product::ware("PowerCells"), 300),
             )))),
         );
     }
 
+    #[test]
+    fn transfering_named_modules() {
+        let recipe_catalog = read_recipe_catalog("default");
+        let mut universe = generate_simple_universe("the_universe".to_string(), &recipe_catalog);
+        universe.set_recipes(recipe_catalog.clone());
+        let module_cargo = recipe_catalog.module_product("PowerToOre").unwrap().clone();
+        universe.constructs.get_mut("The_base_1").unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(module_cargo.clone(), 2))));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CargoTransfered(1)),
+            universe.handle_event(ExternalCommands::try_from(&"TransferCargo transport The_base_1 Module:PowerToOre 1".to_string()).unwrap()),
+        );
+        assert_eq!(Some(&1), universe.constructs.get("transport").unwrap().current_storage().get(&module_cargo));
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("No recipe builds a module named Nope.".to_string())),
+            universe.handle_event(ExternalCommands::try_from(&"TransferCargo transport The_base_1 Module:Nope 1".to_string()).unwrap()),
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::Denied("No recipe builds a module named Nope.".to_string())),
+            universe.handle_event(ExternalCommands::try_from(&"Construct The_base_1 Sell Module:Nope 1 30".to_string()).unwrap()),
+        );
+    }
+
+    #[test]
+    fn arrival_on_the_arrival_turn() {
+        let mut universe = generate_simple_universe("the_universe".to_string(), &read_recipe_catalog("default"));
+        let distant = add_distant_sector(&mut universe);
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::InTransit(4)),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), distant.clone(), Some(0))))),
+        );
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        let in_transit = ConstructPositionStatus::InTransit { from: SectorPosition::new(1, 1, 1), to: distant.clone(), group_address: Some(0), arrival_turn: 4 };
+        for _ in 0..3 {
+            assert!(universe.request_execute_turn());
+            verify_construct(&mut universe, &in_transit, "transport");
+        }
+        assert!(universe.request_execute_turn());
+        assert_eq!(4, universe.time().turn());
+        verify_construct(&mut universe, &InSector(ConstructPositionSector::new(distant, 0)), "transport");
+    }
+
+    #[test]
+    fn docked_constructs_travel_with_their_host() {
+        let mut universe = generate_simple_universe("the_universe".to_string(), &read_recipe_catalog("default"));
+        let distant = add_distant_sector(&mut universe);
+        universe.constructs.get_mut("The_base_1").unwrap().push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 10))));
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::InTransit(4)),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("The_base_1".to_string(), distant.clone(), Some(0))))),
+        );
+
+        let transfer = |universe: &mut MyLittleUniverse| {
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::TransferCargo(OfTransferCargo::new("transport".to_string(), "The_base_1".to_string(), Amount::new(Product::ware("Metals"), 10)))))
+        };
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Cannot transfer, because transport is in transit.".to_string())),
+            transfer(&mut universe),
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(ConstructPositionEventReturnType::Denied("Cannot undock because The_base_1 is in transit.".to_string()))),
+            universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Undock))),
+        );
+
+        finish_travel(&mut universe);
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CargoTransfered(10)), transfer(&mut universe));
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::ConstructPosition(ConstructPositionEventReturnType::RequestProcessed)),
+            universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Undock))),
+        );
+        verify_construct(&mut universe, &InSector(ConstructPositionSector::new(distant, 0)), "transport");
+    }
+
     fn verify_all_constructs_position(universe: &mut MyLittleUniverse, transport_position: ConstructPositionStatus, base_1_position: ConstructPositionStatus, base_2_position: ConstructPositionStatus) {
         verify_construct(universe, &transport_position, "transport");
         verify_sector_position(universe, transport_position, "transport");
@@ -711,6 +1260,17 @@ mod tests_int {
                     assert!(false);
                 }
             }
+            ConstructPositionStatus::InTransit { from, .. } => {
+                assert!(!universe.sectors.get(&from).unwrap().groups().iter().flatten().any(|name| name.eq(construct_name)));
+            }
+        }
+    }
+
+    // Runs turns until no construct is in transit anymore.
+    fn finish_travel(universe: &mut MyLittleUniverse) {
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        while universe.constructs().values().any(|construct| matches!(construct.position().position(), ConstructPositionStatus::InTransit { .. })) {
+            assert!(universe.request_execute_turn());
         }
     }
 }
\ No newline at end of file
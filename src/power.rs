@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::construct_position::ConstructPositionStatus;
+use crate::my_little_universe::MyLittleUniverse;
+
+// The power of the last turn, the grid is the construct that hosts the shared power grid.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub struct PowerState {
+    generated: u32,
+    demand: u32,
+    grid: Option<String>,
+    unpowered_modules: Vec<String>,
+}
+
+impl PowerState {
+    pub fn generated(&self) -> u32 {
+        self.generated
+    }
+    pub fn demand(&self) -> u32 {
+        self.demand
+    }
+    pub fn grid(&self) -> &Option<String> {
+        &self.grid
+    }
+    pub fn unpowered_modules(&self) -> &Vec<String> {
+        &self.unpowered_modules
+    }
+    pub fn is_unpowered(&self, module_name: &str) -> bool {
+        self.unpowered_modules.iter().any(|unpowered| unpowered.eq(module_name))
+    }
+}
+
+struct PowerDemand {
+    priority: u32,
+    construct_name: String,
+    module_name: String,
+    power_draw: u32,
+}
+
+impl MyLittleUniverse {
+    // Docked constructs that share power join the grid of their host, power goes to the modules with the highest priority first.
+    pub(crate) fn power_phase(&mut self, _current_turn: &u64) {
+        let mut grids: HashMap<String, (u32, Vec<PowerDemand>)> = HashMap::new();
+
+        for construct_name in self.construct_names_in_turn_order() {
+            let grid = self.power_grid_of(&construct_name);
+            let construct = self.constructs.get_mut(&construct_name).unwrap();
+            let generated = construct.generate_power();
+            let demands: Vec<PowerDemand> = construct.power_demands().into_iter()
+                .map(|(module_name, priority, power_draw)| PowerDemand { priority, construct_name: construct_name.clone(), module_name, power_draw })
+                .collect();
+            construct.power = PowerState {
+                generated,
+                demand: demands.iter().map(|demand| demand.power_draw).sum(),
+                grid: if grid.eq(&construct_name) { None } else { Some(grid.clone()) },
+                unpowered_modules: Vec::new(),
+            };
+
+            let (power, grid_demands) = grids.entry(grid).or_insert((0, Vec::new()));
+            *power += generated;
+            grid_demands.extend(demands);
+        }
+
+        for (_, (mut power, mut demands)) in grids {
+            // Stable, so equal priorities keep the construct name order.
+            demands.sort_by_key(|demand| std::cmp::Reverse(demand.priority));
+            for demand in demands {
+                if demand.power_draw <= power {
+                    power -= demand.power_draw;
+                } else {
+                    self.constructs.get_mut(&demand.construct_name).unwrap()
+                        .power.unpowered_modules.push(demand.module_name);
+                }
+            }
+        }
+    }
+
+    fn power_grid_of(&self, construct_name: &String) -> String {
+        let construct = self.constructs.get(construct_name).unwrap();
+        match construct.position().position() {
+            ConstructPositionStatus::IsDocked(host_name) if construct.shares_power() => self.power_grid_of(host_name),
+            _ => construct_name.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::construct_position::ExternalConstructPositionEventType;
+    use crate::construct::generator_module::GeneratorModule;
+    use crate::construct::production_module::{IdleReason, ProductionModule};
+    use crate::construct_module::ConstructModuleType::{Generator, Production};
+    use crate::products::Product;
+    use crate::recipes::{read_recipe_catalog, Recipe};
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn modules_need_power_to_produce() {
+        let mut universe = generate_simple_universe("power".to_string(), &read_recipe_catalog("default"));
+        let drill = Recipe::new("Drill".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 2)], 1).with_power_draw(5);
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        assert_eq!(Ok(()), base.install(Production(ProductionModule::from_recipe("Drill".to_string(), &drill, 0))));
+        base.push_event(&ConstructEventType::External(ExternalConstructEventType::SetModuleEnabled { module_name: "PowerToOre".to_string(), enabled: false }));
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 10))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        // Takes its input without power, but cannot finish the batch.
+        for _ in 0..3 {
+            assert!(universe.request_execute_turn());
+        }
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert_eq!(None, base.current_storage().get(&Product::ware("Ores")));
+        assert_eq!(&vec!["Drill".to_string()], base.power().unpowered_modules());
+        assert_eq!(2, base.production_module("Drill").unwrap().stats().idle_turns_for(&IdleReason::NoPower));
+
+        // The transport docks and shares its generator with the base.
+        let transport = universe.constructs.get_mut("transport").unwrap();
+        assert_eq!(Ok(()), transport.install(Generator(GeneratorModule::new("Solar".to_string(), 5, vec![]))));
+        assert_eq!(
+            ExternalCommandReturnValues::Construct(ConstructEvenReturnType::PowerSharingSet),
+            universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::SetPowerSharing(true)))
+        );
+        universe.handle_event(ExternalCommands::Construct("transport".to_string(), ExternalConstructEventType::ConstructPosition(ExternalConstructPositionEventType::Dock("The_base_1".to_string()))));
+        assert!(universe.request_execute_turn());
+
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert_eq!(Some(&2), base.current_storage().get(&Product::ware("Ores")));
+        assert!(base.power().unpowered_modules().is_empty());
+        assert_eq!(&Some("The_base_1".to_string()), universe.constructs().get("transport").unwrap().power().grid());
+    }
+}
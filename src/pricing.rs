@@ -0,0 +1,263 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::construct::Construct;
+use crate::construct_module::ConstructModuleType;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::{Product, ProductRegistry};
+use crate::sector::SectorPosition;
+
+// The stock a construct wants to hold, in batches of what its modules take in and put out.
+const STOCK_BATCHES: u32 = 5;
+const PRICE_HISTORY_TURNS: usize = 5;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum PriceScope {
+    Construct(String),
+    Sector(SectorPosition),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq)]
+pub enum PriceTrend {
+    Rising,
+    Falling,
+    Stable,
+}
+
+// The history holds the prices of the last turns, oldest first.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ProductPrice {
+    product: Product,
+    price: u32,
+    stock: u32,
+    target_stock: u32,
+    history: Vec<u32>,
+    trend: PriceTrend,
+}
+
+impl ProductPrice {
+    pub fn product(&self) -> &Product {
+        &self.product
+    }
+    pub fn price(&self) -> u32 {
+        self.price
+    }
+    pub fn stock(&self) -> u32 {
+        self.stock
+    }
+    pub fn target_stock(&self) -> u32 {
+        self.target_stock
+    }
+    pub fn history(&self) -> &Vec<u32> {
+        &self.history
+    }
+    pub fn trend(&self) -> PriceTrend {
+        self.trend
+    }
+}
+
+#[derive(Default)]
+struct StockLevel {
+    stock: u32,
+    target_stock: u32,
+}
+
+// Twice the base price with an empty stock, the base price at the target stock and cheaper beyond it.
+// Without a target stock nothing is wanted, so the price is the lowest one.
+fn price_of(product: &Product, stock_level: &StockLevel) -> u32 {
+    let base_price = u64::from(ProductRegistry::global().base_price(product));
+    let target_stock = u64::from(stock_level.target_stock);
+    let price = base_price.saturating_mul(2 * target_stock)
+        .checked_div(target_stock + u64::from(stock_level.stock))
+        .unwrap_or(0);
+    u32::try_from(price.max(1)).unwrap_or(u32::MAX)
+}
+
+// Only the products the modules of the construct take in or put out have a price.
+fn add_stock_levels(stock_levels: &mut HashMap<Product, StockLevel>, construct: &Construct) {
+    let mut add_target = |product: &Product, amount: u32| {
+        let stock_level = stock_levels.entry(product.clone()).or_default();
+        stock_level.target_stock = stock_level.target_stock.saturating_add(amount.saturating_mul(STOCK_BATCHES));
+    };
+    for module in construct.modules() {
+        match module {
+            ConstructModuleType::Production(production_module) if production_module.enabled() => {
+                for amount in production_module.input().iter().chain(production_module.leveled_output().iter()) {
+                    add_target(amount.product(), amount.amount());
+                }
+            }
+            ConstructModuleType::Mining(mining_module) => add_target(&mining_module.product(), mining_module.extraction_per_turn()),
+            _ => {}
+        }
+    }
+    for (product, stock_level) in stock_levels.iter_mut() {
+        stock_level.stock = stock_level.stock.saturating_add(construct.current_storage().get(product).copied().unwrap_or(0));
+    }
+}
+
+impl MyLittleUniverse {
+    pub fn prices(&self, price_scope: &PriceScope) -> Result<Vec<ProductPrice>, String> {
+        let constructs_by_sector = match price_scope {
+            PriceScope::Sector(_) => self.constructs_by_sector(),
+            PriceScope::Construct(_) => HashMap::new(),
+        };
+        let current_prices = self.current_prices(price_scope, &constructs_by_sector)?;
+        let history = self.price_history.get(price_scope);
+
+        let mut prices: Vec<ProductPrice> = current_prices.into_iter()
+            .map(|(product, (price, stock_level))| {
+                let history: Vec<u32> = history
+                    .and_then(|history| history.get(&product))
+                    .map_or(Vec::new(), |history| history.iter().copied().collect());
+                let trend = match history.first() {
+                    Some(oldest) if *oldest < price => PriceTrend::Rising,
+                    Some(oldest) if *oldest > price => PriceTrend::Falling,
+                    _ => PriceTrend::Stable,
+                };
+                ProductPrice { product, price, stock: stock_level.stock, target_stock: stock_level.target_stock, history, trend }
+            })
+            .collect();
+        prices.sort_by_key(|price| price.product.to_string());
+        Ok(prices)
+    }
+
+    // The history of a scope is kept from the first time its prices are asked for.
+    pub(crate) fn watch_prices(&mut self, price_scope: PriceScope) {
+        self.price_history.entry(price_scope).or_default();
+    }
+
+    // Docked constructs are in the sector of their host, constructs in transit are in none.
+    fn constructs_by_sector(&self) -> HashMap<SectorPosition, Vec<&Construct>> {
+        let mut constructs_by_sector: HashMap<SectorPosition, Vec<&Construct>> = HashMap::new();
+        for (construct_name, construct) in &self.constructs {
+            if let Some(position) = self.get_sector_position(construct_name.clone()) {
+                constructs_by_sector.entry(position.sector_position().clone()).or_default().push(construct);
+            }
+        }
+        constructs_by_sector
+    }
+
+    // A sector prices the stock of all its constructs against the needs of all its constructs.
+    fn current_prices(&self, price_scope: &PriceScope, constructs_by_sector: &HashMap<SectorPosition, Vec<&Construct>>) -> Result<HashMap<Product, (u32, StockLevel)>, String> {
+        let mut stock_levels = HashMap::new();
+        match price_scope {
+            PriceScope::Construct(construct_name) => {
+                match self.constructs.get(construct_name) {
+                    Some(construct) => add_stock_levels(&mut stock_levels, construct),
+                    None => return Err(format!("No construct named {}.", construct_name))
+                }
+            }
+            PriceScope::Sector(sector_position) => {
+                if !self.sectors.contains_key(sector_position) {
+                    return Err(format!("No sector at {:?}.", sector_position));
+                }
+                for construct in constructs_by_sector.get(sector_position).into_iter().flatten() {
+                    let mut construct_stock_levels = HashMap::new();
+                    add_stock_levels(&mut construct_stock_levels, construct);
+                    for (product, construct_stock_level) in construct_stock_levels {
+                        let stock_level: &mut StockLevel = stock_levels.entry(product).or_default();
+                        stock_level.stock = stock_level.stock.saturating_add(construct_stock_level.stock);
+                        stock_level.target_stock = stock_level.target_stock.saturating_add(construct_stock_level.target_stock);
+                    }
+                }
+            }
+        }
+        Ok(stock_levels.into_iter()
+            .map(|(product, stock_level)| (product.clone(), (price_of(&product, &stock_level), stock_level)))
+            .collect())
+    }
+
+    // Only the watched scopes are priced, a scope whose construct or sector is gone is dropped.
+    pub(crate) fn record_prices(&mut self, _current_turn: &u64) {
+        if self.price_history.is_empty() {
+            return;
+        }
+        let mut price_history = std::mem::take(&mut self.price_history);
+        let constructs_by_sector = self.constructs_by_sector();
+        price_history.retain(|price_scope, history| {
+            let current_prices = match self.current_prices(price_scope, &constructs_by_sector) {
+                Ok(current_prices) => current_prices,
+                Err(_) => return false
+            };
+            history.retain(|product, _| current_prices.contains_key(product));
+            for (product, (price, _)) in current_prices {
+                let product_history: &mut VecDeque<u32> = history.entry(product).or_default();
+                if product_history.len() == PRICE_HISTORY_TURNS {
+                    product_history.pop_front();
+                }
+                product_history.push_back(price);
+            }
+            true
+        });
+        self.price_history = price_history;
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEventType, InternalConstructEventType};
+    use crate::my_little_universe::{ExternalUniverseEventType, MyLittleUniverse, MyLittleUniverseReturnValues};
+    use crate::pricing::{price_of, PriceScope, PriceTrend, StockLevel};
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::sector::SectorPosition;
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn prices_follow_the_stock() {
+        let mut universe = generate_simple_universe("pricing".to_string(), &read_recipe_catalog("default"));
+        let base = PriceScope::Construct("The_base_1".to_string());
+        let sector = PriceScope::Sector(SectorPosition::new(1, 1, 1));
+        let get_prices = |universe: &mut MyLittleUniverse, price_scope: &PriceScope| match universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GetPrices(price_scope.clone()))) {
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Prices(prices)) => prices,
+            return_value => panic!("Expected the prices, got {:?}", return_value)
+        };
+
+        // PowerToOre takes 1 power cell and makes 2 ores, so it wants 5 power cells and 10 ores.
+        let prices = get_prices(&mut universe, &base);
+        get_prices(&mut universe, &sector);
+        assert_eq!(vec![Product::ware("Ores"), Product::ware("PowerCells")], prices.iter().map(|price| price.product().clone()).collect::<Vec<Product>>());
+        assert_eq!(10, prices[0].price());
+        assert_eq!(20, prices[1].price());
+
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 9))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+        for _ in 0..3 {
+            assert!(universe.request_execute_turn());
+        }
+
+        // The power cells are used up and the ores pile up.
+        let prices = universe.prices(&base).unwrap();
+        assert_eq!(4, prices[0].stock());
+        assert_eq!(7, prices[0].price());
+        assert_eq!(&vec![10, 8, 7], prices[0].history());
+        assert_eq!(PriceTrend::Falling, prices[0].trend());
+        assert_eq!(6, prices[1].stock());
+        assert_eq!(&vec![7, 8, 9], prices[1].history());
+        assert_eq!(PriceTrend::Rising, prices[1].trend());
+
+        // The transport has no modules, so the sector prices are the ones of the base.
+        assert_eq!(prices, get_prices(&mut universe, &sector));
+        // Only the scopes that were asked for keep a history.
+        assert_eq!(2, universe.price_history.len());
+        assert!(universe.prices(&PriceScope::Construct("The_base_2".to_string())).unwrap().iter().all(|price| price.history().is_empty()));
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("No construct named nobody.".to_string())),
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::GetPrices(PriceScope::Construct("nobody".to_string()))))
+        );
+    }
+
+    #[test]
+    fn prices_of_extreme_stock_levels() {
+        let ores = Product::ware("Ores");
+        assert_eq!(1, price_of(&ores, &StockLevel::default()));
+        assert_eq!(1, price_of(&ores, &StockLevel { stock: 10, target_stock: 0 }));
+        assert_eq!(10, price_of(&ores, &StockLevel { stock: 0, target_stock: u32::MAX }));
+        assert_eq!(5, price_of(&ores, &StockLevel { stock: u32::MAX, target_stock: u32::MAX }));
+    }
+}
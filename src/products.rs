@@ -1,10 +1,385 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::ops::Add;
+use std::sync::OnceLock;
+
 use serde::{Deserialize, Serialize};
+
 use crate::construct_module::ConstructModuleType;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::sector::SectorPosition;
 
+// Untagged so a ware is just its id in json, that keeps it usable as a key in storage maps.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+#[serde(untagged)]
 pub enum Product {
-    Ores,
-    Metals,
-    PowerCells,
-    Module(ConstructModuleType)
-}
\ No newline at end of file
+    Ware(String),
+    // Boxed, a module is much bigger than a ware id.
+    Module(Box<ConstructModuleType>),
+}
+
+impl Product {
+    pub fn ware(id: &str) -> Self {
+        Product::Ware(id.to_string())
+    }
+
+    pub fn module(module: ConstructModuleType) -> Self {
+        Product::Module(Box::new(module))
+    }
+
+    // Commands name module cargo as Module:<name>, the universe swaps it for the module its recipes build under that name.
+    pub fn module_named(module_name: &str) -> Self {
+        Product::Ware(format!("Module:{}", module_name))
+    }
+
+    pub fn named_module(&self) -> Option<&str> {
+        match self {
+            Product::Ware(id) => id.strip_prefix("Module:"),
+            Product::Module(_) => None
+        }
+    }
+
+    pub fn volume(&self) -> u32 {
+        ProductRegistry::global().volume(self)
+    }
+
+    pub fn category(&self) -> ProductCategory {
+        ProductRegistry::global().category(self)
+    }
+}
+
+impl Display for Product {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Product::Ware(id) => write!(f, "{}", id),
+            Product::Module(module) => write!(f, "Module({})", module.name())
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum ProductCategory {
+    Bulk,
+    Energy,
+    Modules,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ProductDefinition {
+    id: String,
+    display_name: String,
+    category: ProductCategory,
+    volume: u32,
+    // The price when the stock is just what the production modules need.
+    #[serde(default = "default_base_price")]
+    base_price: u32,
+}
+
+fn default_base_price() -> u32 {
+    10
+}
+
+impl ProductDefinition {
+    pub fn new(id: String, display_name: String, category: ProductCategory, volume: u32) -> Self {
+        ProductDefinition { id, display_name, category, volume, base_price: default_base_price() }
+    }
+
+    pub fn with_base_price(mut self, base_price: u32) -> Self {
+        self.base_price = base_price;
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+    pub fn category(&self) -> ProductCategory {
+        self.category
+    }
+    pub fn volume(&self) -> u32 {
+        self.volume
+    }
+    pub fn base_price(&self) -> u32 {
+        self.base_price
+    }
+}
+
+static PRODUCT_REGISTRY: OnceLock<ProductRegistry> = OnceLock::new();
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ProductRegistry {
+    products: Vec<ProductDefinition>,
+    module_volume: u32,
+}
+
+impl ProductRegistry {
+    pub fn new(products: Vec<ProductDefinition>, module_volume: u32) -> Self {
+        ProductRegistry { products, module_volume }
+    }
+
+    // The registry from the config given at startup, or the built in products when none were given.
+    pub fn global() -> &'static ProductRegistry {
+        PRODUCT_REGISTRY.get_or_init(ProductRegistry::built_in)
+    }
+
+    // The products cannot change while a universe is running, so a process can only use the products of one config.
+    pub fn set_global(registry: ProductRegistry) -> Result<(), String> {
+        match PRODUCT_REGISTRY.set(registry) {
+            Err(registry) if registry.ne(ProductRegistry::global()) => Err("Other products are already defined, a process can only use the products of one config.".to_string()),
+            _ => Ok(())
+        }
+    }
+
+    fn built_in() -> Self {
+        ProductRegistry::new(vec![
+            ProductDefinition::new("Ores".to_string(), "Ores".to_string(), ProductCategory::Bulk, 1).with_base_price(5),
+            ProductDefinition::new("Metals".to_string(), "Metals".to_string(), ProductCategory::Bulk, 1).with_base_price(15),
+            ProductDefinition::new("PowerCells".to_string(), "Power cells".to_string(), ProductCategory::Energy, 1),
+        ], 10)
+    }
+
+    pub fn products(&self) -> &Vec<ProductDefinition> {
+        &self.products
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ProductDefinition> {
+        self.products.iter().find(|definition| definition.id.eq(id))
+    }
+
+    pub fn product(&self, id: &str) -> Option<Product> {
+        self.get(id).map(|definition| Product::ware(definition.id()))
+    }
+
+    // The universe is validated against the registry when it is loaded, unknown wares only show up in tests.
+    pub fn volume(&self, product: &Product) -> u32 {
+        match product {
+            Product::Ware(id) => self.get(id).map_or(1, |definition| definition.volume),
+            Product::Module(_) => self.module_volume
+        }
+    }
+
+    // Modules and unknown wares use the default base price.
+    pub fn base_price(&self, product: &Product) -> u32 {
+        match product {
+            Product::Ware(id) => self.get(id).map_or(default_base_price(), |definition| definition.base_price),
+            Product::Module(_) => default_base_price()
+        }
+    }
+
+    pub fn category(&self, product: &Product) -> ProductCategory {
+        match product {
+            Product::Ware(id) => self.get(id).map_or(ProductCategory::Bulk, |definition| definition.category),
+            Product::Module(_) => ProductCategory::Modules
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let mut ids = HashSet::new();
+
+        for definition in &self.products {
+            if definition.id.is_empty() || definition.id.contains(' ') {
+                errors.push(format!("Product id \"{}\" cannot be empty or contain spaces.", definition.id));
+            }
+            if !ids.insert(definition.id.as_str()) {
+                errors.push(format!("Product id {} is used more than once.", definition.id));
+            }
+            if definition.volume == 0 {
+                errors.push(format!("Product {} needs a volume of at least 1.", definition.id));
+            }
+            if definition.base_price == 0 {
+                errors.push(format!("Product {} needs a base price of at least 1.", definition.id));
+            }
+        }
+        if self.module_volume == 0 {
+            errors.push("Modules need a volume of at least 1.".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl MyLittleUniverse {
+    // Every ware the catalogs and the universe use has to be defined, a typo would otherwise become a new ware.
+    pub fn validate_products(&self, product_registry: &ProductRegistry) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let mut check = |user: String, product: &Product| {
+            if let Product::Ware(id) = product {
+                if product_registry.get(id).is_none() {
+                    let error = format!("{} uses the unknown product {}.", user, id);
+                    if !errors.contains(&error) {
+                        errors.push(error);
+                    }
+                }
+            }
+        };
+
+        for recipe in self.recipes.recipes() {
+            for amount in recipe.input().iter().chain(recipe.output().iter()) {
+                check(format!("Recipe {}", recipe.id()), amount.product());
+            }
+        }
+        for blueprint in self.blueprints.blueprints() {
+            for amount in blueprint.cost() {
+                check(format!("Blueprint {}", blueprint.id()), amount.product());
+            }
+            for module in blueprint.modules() {
+                if let ConstructModuleType::Mining(mining_module) = module {
+                    check(format!("Blueprint {}", blueprint.id()), &mining_module.product());
+                }
+            }
+        }
+        for module_level in self.module_levels.levels() {
+            for amount in module_level.cost() {
+                check(format!("Module level {}", module_level.level()), amount.product());
+            }
+        }
+        for amount in self.wear.maintenance_cost() {
+            check("Maintenance".to_string(), amount.product());
+        }
+
+        let mut sector_positions: Vec<&SectorPosition> = self.sectors.keys().collect();
+        sector_positions.sort_by_key(|sector_position| format!("{:?}", sector_position));
+        for sector_position in sector_positions {
+            for deposit in self.sectors.get(sector_position).unwrap().deposits() {
+                check(format!("Sector {:?}", sector_position), deposit.product());
+            }
+        }
+        for construct_name in self.construct_names_in_turn_order() {
+            let construct = self.constructs.get(&construct_name).unwrap();
+            for product in construct.current_storage().keys() {
+                check(format!("Construct {}", construct_name), product);
+            }
+            for module in construct.modules() {
+                if let ConstructModuleType::Mining(mining_module) = module {
+                    check(format!("Construct {}", construct_name), &mining_module.product());
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub fn read_product_registry(config_name: &str) -> ProductRegistry {
+    let config_folder = "./config/".to_string().add(config_name);
+
+    let products_config_path = config_folder.to_string().add("/products.json");
+    println!("Using products config products_config_path: {}", products_config_path);
+
+    let products_config = fs::read_to_string(&products_config_path)
+        .unwrap_or_else(|_| panic!("Something went wrong reading the file {}", products_config_path));
+
+    let product_registry: ProductRegistry = serde_json::from_str(products_config.as_str())
+        .unwrap_or_else(|_| panic!("Something went wrong parsing the file {}", products_config_path));
+
+    if let Err(errors) = product_registry.validate() {
+        panic!("The products in {} are not valid: {:?}", products_config_path, errors);
+    }
+
+    product_registry
+}
+
+#[cfg(test)]
+mod tests_int {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::construct::amount::Amount;
+    use crate::construct::mining_module::MiningModule;
+    use crate::construct::production_module::ProductionModule;
+    use crate::construct_module::ConstructModuleType;
+    use crate::construct_module::ConstructModuleType::Mining;
+    use crate::products::{Product, ProductCategory, ProductDefinition, ProductRegistry, read_product_registry};
+    use crate::recipes::read_recipe_catalog;
+    use crate::sector::{ResourceDeposit, SectorPosition};
+    use crate::universe_generator::generate_simple_universe;
+    use crate::wear::WearCatalog;
+
+    #[test]
+    fn default_config_matches_built_in() {
+        assert_eq!(ProductRegistry::built_in(), read_product_registry("default"));
+        assert_eq!(ProductRegistry::built_in(), read_product_registry("performance"));
+    }
+
+    #[test]
+    fn volume_and_category() {
+        let registry = ProductRegistry::new(vec![
+            ProductDefinition::new("Ores".to_string(), "Ores".to_string(), ProductCategory::Bulk, 1),
+            ProductDefinition::new("Crates".to_string(), "Crates of stuff".to_string(), ProductCategory::Bulk, 5),
+        ], 20);
+        let module = Product::module(ConstructModuleType::Production(ProductionModule::new("Factory".to_string(), vec![], vec![], 1, 0)));
+
+        assert_eq!(Some(Product::ware("Crates")), registry.product("Crates"));
+        assert_eq!(None, registry.product("PowerCells"));
+        assert_eq!(5, registry.volume(&Product::ware("Crates")));
+        assert_eq!(1, registry.volume(&Product::ware("Unknown")));
+        assert_eq!(20, registry.volume(&module));
+        assert_eq!(ProductCategory::Modules, registry.category(&module));
+        assert_eq!(Ok(()), registry.validate());
+
+        assert_eq!(
+            Err(vec![
+                "Product id \"Two words\" cannot be empty or contain spaces.".to_string(),
+                "Product id Ores is used more than once.".to_string(),
+                "Product Ores needs a volume of at least 1.".to_string(),
+                "Modules need a volume of at least 1.".to_string(),
+            ]),
+            ProductRegistry::new(vec![
+                ProductDefinition::new("Two words".to_string(), "".to_string(), ProductCategory::Bulk, 1),
+                ProductDefinition::new("Ores".to_string(), "".to_string(), ProductCategory::Bulk, 1),
+                ProductDefinition::new("Ores".to_string(), "".to_string(), ProductCategory::Bulk, 0),
+            ], 0).validate()
+        );
+    }
+
+    #[test]
+    fn only_one_set_of_products_per_process() {
+        assert_eq!(Ok(()), ProductRegistry::set_global(ProductRegistry::built_in()));
+        assert_eq!(
+            Err("Other products are already defined, a process can only use the products of one config.".to_string()),
+            ProductRegistry::set_global(ProductRegistry::new(vec![], 10))
+        );
+    }
+
+    #[test]
+    fn universe_only_uses_known_products() {
+        let recipe_catalog = read_recipe_catalog("default");
+        let mut universe = generate_simple_universe("known_products".to_string(), &recipe_catalog);
+        universe.set_recipes(recipe_catalog);
+        assert_eq!(Ok(()), universe.validate_products(&ProductRegistry::built_in()));
+
+        universe.sectors.get_mut(&SectorPosition::new(2, 2, 2)).unwrap().add_deposit(ResourceDeposit::new(0, Product::ware("Ice"), 10));
+        assert_eq!(Ok(()), universe.constructs.get_mut("transport").unwrap().install(Mining(MiningModule::new("IceDrill".to_string(), "Ice".to_string(), 1))));
+        universe.set_wear(WearCatalog::new(vec![], vec![Amount::new(Product::ware("Spares"), 1)], 10));
+        assert_eq!(
+            Err(vec![
+                "Maintenance uses the unknown product Spares.".to_string(),
+                "Sector SectorPosition { x: 2, y: 2, z: 2 } uses the unknown product Ice.".to_string(),
+                "Construct transport uses the unknown product Ice.".to_string(),
+            ]),
+            universe.validate_products(&ProductRegistry::built_in())
+        );
+    }
+
+    #[test]
+    fn wares_are_plain_json_keys() {
+        let mut storage = HashMap::new();
+        storage.insert(Product::ware("Ores"), 3);
+        let json = json!(storage).to_string();
+        assert_eq!("{\"Ores\":3}", json);
+        assert_eq!(storage, serde_json::from_str::<HashMap<Product, u32>>(&json).unwrap());
+    }
+}
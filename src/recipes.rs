@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::amount::Amount;
+use crate::construct::production_module::ProductionModule;
+use crate::construct_module::ConstructModuleType;
+use crate::products::Product;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct Recipe {
+    id: String,
+    input: Vec<Amount>,
+    output: Vec<Amount>,
+    production_time: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    module_output: Vec<ModuleOutput>,
+    // Power used every turn a module with this recipe is producing.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    power_draw: u32,
+}
+
+fn is_zero(power_draw: &u32) -> bool {
+    *power_draw == 0
+}
+
+// A production module, running the referenced recipe, that is produced as cargo.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct ModuleOutput {
+    name: String,
+    recipe_id: String,
+    amount: u32,
+}
+
+impl ModuleOutput {
+    pub fn new(name: String, recipe_id: String, amount: u32) -> Self {
+        ModuleOutput { name, recipe_id, amount }
+    }
+}
+
+impl Recipe {
+    pub fn new(id: String, input: Vec<Amount>, output: Vec<Amount>, production_time: u32) -> Self {
+        Recipe { id, input, output, production_time, module_output: Vec::new(), power_draw: 0 }
+    }
+
+    pub fn with_module_output(mut self, module_output: Vec<ModuleOutput>) -> Self {
+        self.module_output = module_output;
+        self
+    }
+
+    pub fn with_power_draw(mut self, power_draw: u32) -> Self {
+        self.power_draw = power_draw;
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    pub fn input(&self) -> &Vec<Amount> {
+        &self.input
+    }
+    pub fn output(&self) -> &Vec<Amount> {
+        &self.output
+    }
+    pub fn production_time(&self) -> u32 {
+        self.production_time
+    }
+    pub fn module_output(&self) -> &Vec<ModuleOutput> {
+        &self.module_output
+    }
+    pub fn power_draw(&self) -> u32 {
+        self.power_draw
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub struct RecipeCatalog {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeCatalog {
+    pub fn new(recipes: Vec<Recipe>) -> Self {
+        RecipeCatalog { recipes }
+    }
+
+    pub fn recipes(&self) -> &Vec<Recipe> {
+        &self.recipes
+    }
+
+    pub fn get(&self, recipe_id: &str) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| recipe.id.eq(recipe_id))
+    }
+
+    // The module cargo some recipe outputs under that name.
+    pub fn module_product(&self, module_name: &str) -> Option<&Product> {
+        self.recipes.iter()
+            .flat_map(|recipe| recipe.output.iter())
+            .map(|amount| amount.product())
+            .find(|product| matches!(product, Product::Module(module) if module.name().eq(module_name)))
+    }
+
+    // Turns the module outputs into module products, so production modules can store them like any other output.
+    pub fn resolve_module_outputs(mut self) -> Self {
+        let catalog = self.clone();
+        for recipe in &mut self.recipes {
+            for module_output in recipe.module_output.drain(..) {
+                let module_recipe = catalog.get(&module_output.recipe_id)
+                    .expect("Module outputs should only reference known recipes, validate the catalog first");
+                let module = ConstructModuleType::Production(ProductionModule::from_recipe(module_output.name, module_recipe, 0));
+                recipe.output.push(Amount::new(Product::module(module), module_output.amount));
+            }
+        }
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let mut ids = HashSet::new();
+
+        for recipe in &self.recipes {
+            if recipe.id.is_empty() {
+                errors.push("Recipe id cannot be empty.".to_string());
+            }
+            if !ids.insert(recipe.id.as_str()) {
+                errors.push(format!("Recipe id {} is used more than once.", recipe.id));
+            }
+            if recipe.output.is_empty() && recipe.module_output.is_empty() {
+                errors.push(format!("Recipe {} has no output.", recipe.id));
+            }
+            if recipe.production_time == 0 {
+                errors.push(format!("Recipe {} needs a production_time of at least 1.", recipe.id));
+            }
+            for amount in recipe.input.iter().chain(recipe.output.iter()) {
+                if amount.amount() == 0 {
+                    errors.push(format!("Recipe {} has an amount of zero {}.", recipe.id, amount.product()));
+                }
+            }
+            for module_output in &recipe.module_output {
+                match self.get(&module_output.recipe_id) {
+                    None => errors.push(format!("Recipe {} outputs module {} with the unknown recipe {}.", recipe.id, module_output.name, module_output.recipe_id)),
+                    Some(module_recipe) if !module_recipe.module_output.is_empty() => {
+                        errors.push(format!("Recipe {} outputs module {} with recipe {}, that itself outputs modules.", recipe.id, module_output.name, module_output.recipe_id));
+                    }
+                    Some(_) => {}
+                }
+                if module_output.amount == 0 {
+                    errors.push(format!("Recipe {} has an amount of zero module {}.", recipe.id, module_output.name));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub fn read_recipe_catalog(config_name: &str) -> RecipeCatalog {
+    let config_folder = "./config/".to_string().add(config_name);
+
+    let recipes_config_path = config_folder.to_string().add("/recipes.json");
+    println!("Using recipes config recipes_config_path: {}", recipes_config_path);
+
+    let recipes_config = fs::read_to_string(&recipes_config_path)
+        .unwrap_or_else(|_| panic!("Something went wrong reading the file {}", recipes_config_path));
+
+    let recipe_catalog: RecipeCatalog = serde_json::from_str(recipes_config.as_str())
+        .unwrap_or_else(|_| panic!("Something went wrong parsing the file {}", recipes_config_path));
+
+    if let Err(errors) = recipe_catalog.validate() {
+        panic!("The recipes in {} are not valid: {:?}", recipes_config_path, errors);
+    }
+
+    recipe_catalog.resolve_module_outputs()
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{Construct, ConstructEvenReturnType, ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::construct_position::ConstructPositionSector;
+    use crate::construct::production_module::ProductionModule;
+    use crate::construct_module::ConstructModuleType;
+    use crate::products::Product;
+    use crate::recipes::{ModuleOutput, read_recipe_catalog, Recipe, RecipeCatalog};
+    use crate::sector::SectorPosition;
+
+    #[test]
+    fn read_default_recipes() {
+        let recipe_catalog = read_recipe_catalog("default");
+        assert_eq!(
+            Some(&Recipe::new("PowerToOre".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 2)], 1)),
+            recipe_catalog.get("PowerToOre")
+        );
+        assert_eq!(None, recipe_catalog.get("Unknown"));
+
+        assert_eq!(3, read_recipe_catalog("performance").recipes().len());
+    }
+
+    #[test]
+    fn module_factory_produces_installable_modules() {
+        let recipe_catalog = read_recipe_catalog("default");
+        let power_to_ore = ProductionModule::from_recipe("PowerToOre".to_string(), recipe_catalog.get("PowerToOre").unwrap(), 0);
+        let module_cargo = Product::module(ConstructModuleType::Production(power_to_ore.clone()));
+        assert_eq!(&vec![Amount::new(module_cargo.clone(), 1)], recipe_catalog.get("PowerToOreModule").unwrap().output());
+
+        let sector_position = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
+        let mut factory = Construct::new("Factory".to_string(), 100, sector_position);
+        assert_eq!(Ok(()), factory.install(ConstructModuleType::Production(ProductionModule::from_recipe("ModuleFactory".to_string(), recipe_catalog.get("PowerToOreModule").unwrap(), 0))));
+        factory.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 5))));
+        factory.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 5))));
+
+        for turn in 1..=6 {
+            factory.push_event(&ConstructEventType::Internal(InternalConstructEventType::ExecuteTurn(turn)));
+        }
+        assert_eq!(Some(&1), factory.current_storage().get(&module_cargo));
+
+        assert_eq!(
+            ConstructEvenReturnType::ModuleInstalled,
+            factory.push_event(&ConstructEventType::External(ExternalConstructEventType::InstallModule { module_name: "PowerToOre".to_string(), installed_name: None, price: None }))
+        );
+        assert_eq!(Some(&power_to_ore), factory.production_module("PowerToOre"));
+    }
+
+    #[test]
+    fn validation_finds_all_errors() {
+        let recipe_catalog = RecipeCatalog::new(vec![
+            Recipe::new("Twice".to_string(), vec![], vec![Amount::new(Product::ware("Ores"), 1)], 1),
+            Recipe::new("Twice".to_string(), vec![Amount::new(Product::ware("Ores"), 0)], vec![], 0),
+        ]);
+
+        assert_eq!(
+            Err(vec![
+                "Recipe id Twice is used more than once.".to_string(),
+                "Recipe Twice has no output.".to_string(),
+                "Recipe Twice needs a production_time of at least 1.".to_string(),
+                "Recipe Twice has an amount of zero Ores.".to_string(),
+            ]),
+            recipe_catalog.validate()
+        );
+
+        let recipe_catalog = RecipeCatalog::new(vec![
+            Recipe::new("Factory".to_string(), vec![], vec![], 1).with_module_output(vec![ModuleOutput::new("Mine".to_string(), "Unknown".to_string(), 1)]),
+            Recipe::new("FactoryFactory".to_string(), vec![], vec![], 1).with_module_output(vec![ModuleOutput::new("Factory".to_string(), "Factory".to_string(), 0)]),
+        ]);
+
+        assert_eq!(
+            Err(vec![
+                "Recipe Factory outputs module Mine with the unknown recipe Unknown.".to_string(),
+                "Recipe FactoryFactory outputs module Factory with recipe Factory, that itself outputs modules.".to_string(),
+                "Recipe FactoryFactory has an amount of zero module Factory.".to_string(),
+            ]),
+            recipe_catalog.validate()
+        );
+    }
+
+    #[test]
+    fn modules_follow_the_rebalanced_catalog() {
+        let recipe_catalog = read_recipe_catalog("default");
+        let power_to_ore = ProductionModule::from_recipe("PowerToOre".to_string(), recipe_catalog.get("PowerToOre").unwrap(), 0);
+        let mut construct = Construct::new("Factory".to_string(), 500, ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0));
+        assert_eq!(Ok(()), construct.install(ConstructModuleType::Production(power_to_ore.clone())));
+        construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::module(ConstructModuleType::Production(power_to_ore)), 2))));
+
+        let rebalanced = Recipe::new("PowerToOre".to_string(), vec![Amount::new(Product::ware("PowerCells"), 1)], vec![Amount::new(Product::ware("Ores"), 5)], 3);
+        construct.refresh_recipes(&RecipeCatalog::new(vec![rebalanced.clone()]));
+
+        let installed = construct.production_module("PowerToOre").unwrap();
+        assert_eq!(&vec![Amount::new(Product::ware("Ores"), 5)], installed.output());
+        assert_eq!(3, installed.production_time());
+        let refreshed_cargo = Product::module(ConstructModuleType::Production(ProductionModule::from_recipe("PowerToOre".to_string(), &rebalanced, 0)));
+        assert_eq!(Some(&2), construct.current_storage().get(&refreshed_cargo));
+        assert_eq!(1, construct.current_storage().len());
+    }
+}
@@ -7,9 +7,17 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use crate::construct::construct::Construct;
+use crate::contracts::ContractBoard;
+use crate::credits::Credits;
 use crate::MainConfig;
+use crate::blueprints::read_blueprint_catalog;
+use crate::module_levels::read_module_level_catalog;
 use crate::my_little_universe::MyLittleUniverse;
+use crate::products::{ProductRegistry, read_product_registry};
+use crate::recipes::read_recipe_catalog;
+use crate::sector::{Sector, SectorPosition};
 use crate::time::TimeStackState;
+use crate::wear::read_wear_catalog;
 use crate::universe_generator::generate_universe;
 
 #[derive(Clone, PartialEq, Debug)]
@@ -43,6 +51,54 @@ fn load_time(universe_name: &String) -> TimeStackState {
     serde_json::from_str(&content).expect("Fauled to parse loaded time save file")
 }
 
+impl Credits {
+    pub fn save(&self, universe_name: &String) {
+        let file_path = format!("{}credits.json", save_file_path(universe_name));
+        let mut file = File::create(file_path)
+            .expect("Failed to create credits save file");
+        file.write_all(format!("{}", json!(self)).as_bytes())
+            .expect("Had problem saving credits");
+    }
+}
+
+// Saves from before the credits have no credits file, they start without wallets.
+fn load_credits(universe_name: &String) -> Credits {
+    let file_path = format!("./save/{}/credits.json", universe_name);
+    if !Path::new(&file_path).is_file() {
+        return Credits::default();
+    }
+    let mut file = File::open(&file_path)
+        .unwrap_or_else(|_| panic!("Filed to open credits save file: {}", &file_path));
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .expect("Failed to load credits data");
+    serde_json::from_str(&content).expect("Failed to parse loaded credits save file")
+}
+
+impl ContractBoard {
+    pub fn save(&self, universe_name: &String) {
+        let file_path = format!("{}contracts.json", save_file_path(universe_name));
+        let mut file = File::create(file_path)
+            .expect("Failed to create contracts save file");
+        file.write_all(format!("{}", json!(self)).as_bytes())
+            .expect("Had problem saving contracts");
+    }
+}
+
+// Saves from before the contracts have no contracts file, they start with an empty board.
+fn load_contract_board(universe_name: &String) -> ContractBoard {
+    let file_path = format!("./save/{}/contracts.json", universe_name);
+    if !Path::new(&file_path).is_file() {
+        return ContractBoard::default();
+    }
+    let mut file = File::open(&file_path)
+        .unwrap_or_else(|_| panic!("Filed to open contracts save file: {}", &file_path));
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .expect("Failed to load contracts data");
+    serde_json::from_str(&content).expect("Failed to parse loaded contracts save file")
+}
+
 fn save_file_path(universe_name: &String) -> String {
     let path = format!("./save/{}/", universe_name);
     create_dir_all(&path).expect("Hard trouble creating save game folder.");
@@ -52,13 +108,19 @@ fn save_file_path(universe_name: &String) -> String {
 impl MyLittleUniverse {
     pub fn save(&self) -> ExternalSaveLoadReturnValue {
         self.time().save(&self.universe_name().to_string());
+        self.credits().save(&self.universe_name().to_string());
+        self.contract_board().save(&self.universe_name().to_string());
         Self::save_constructs(self, &self.universe_name().to_string());
+        Self::save_sectors(self, &self.universe_name().to_string());
         ExternalSaveLoadReturnValue::UniverseIsSaved
     }
 
     pub fn save_as(&self, new_universe_name: &String) -> ExternalSaveLoadReturnValue {
         self.time().save(new_universe_name);
+        self.credits().save(new_universe_name);
+        self.contract_board().save(new_universe_name);
         Self::save_constructs(self, new_universe_name);
+        Self::save_sectors(self, new_universe_name);
         ExternalSaveLoadReturnValue::UniverseIsSaved
     }
 
@@ -82,12 +144,26 @@ impl MyLittleUniverse {
         }
         file.write_all("}".as_bytes()).expect("Had trouble saving station to file.");
     }
+
+    // A list, because the sector positions cannot be json keys.
+    fn save_sectors(&self, universe_name: &String) {
+        let file_path = format!("{}sectors.json", save_file_path(universe_name));
+        let mut file = File::create(&file_path)
+            .unwrap_or_else(|_| panic!("Failed to create sectors save file, got: {}", &file_path));
+        let sectors: Vec<&Sector> = self.sectors().values().collect();
+        file.write_all(format!("{}", json!(sectors)).as_bytes())
+            .expect("Had trouble saving sectors to file.");
+    }
 }
 
 pub fn load_universe(universe_name: String) -> MyLittleUniverse {
     let time = load_time(&universe_name);
     let constructs = load_constructs(&universe_name);
-    MyLittleUniverse::new(universe_name.clone(), time, constructs, HashMap::new())
+    let sectors = load_sectors(&universe_name);
+    let mut universe = MyLittleUniverse::new(universe_name.clone(), time, constructs, sectors);
+    universe.set_credits(load_credits(&universe_name));
+    universe.set_contract_board(load_contract_board(&universe_name));
+    universe
 }
 
 fn load_constructs(universe_name: &String) -> HashMap<String, Construct> {
@@ -105,14 +181,45 @@ fn load_constructs(universe_name: &String) -> HashMap<String, Construct> {
     constructs
 }
 
+// Saves from before the sectors have no sectors file, they start without sectors.
+fn load_sectors(universe_name: &String) -> HashMap<SectorPosition, Sector> {
+    let file_path = format!("./save/{}/sectors.json", universe_name);
+    if !Path::new(&file_path).is_file() {
+        return HashMap::new();
+    }
+    let mut file = File::open(&file_path)
+        .unwrap_or_else(|_| panic!("Filed to open sectors save file, got {}", &file_path));
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .expect("Failed to load sectors data");
+    let sectors: Vec<Sector> = serde_json::from_str(&content).expect("Failed to parse loaded sectors save file");
+    sectors.into_iter()
+        .map(|sector| (sector.position().clone(), sector))
+        .collect()
+}
+
 pub fn load_or_create_universe(config: &MainConfig) -> MyLittleUniverse {
     let save_file_path = format!("./save/{}/", config.universe_name());
+    let product_registry = read_product_registry(config.config_name());
+    if let Err(message) = ProductRegistry::set_global(product_registry.clone()) {
+        panic!("Could not use the products of config {}: {}", config.config_name(), message);
+    }
+    let recipe_catalog = read_recipe_catalog(config.config_name());
 
-    return if Path::new(&save_file_path).is_dir() {
+    let mut universe = if Path::new(&save_file_path).is_dir() {
         load_universe(config.universe_name().to_string())
     } else {
-        generate_universe(config)
+        generate_universe(config, &recipe_catalog)
     };
+    universe.set_module_levels(read_module_level_catalog(config.config_name()));
+    universe.set_blueprints(read_blueprint_catalog(config.config_name(), &recipe_catalog));
+    universe.set_recipes(recipe_catalog);
+    universe.refresh_recipes();
+    universe.set_wear(read_wear_catalog(config.config_name()));
+    if let Err(errors) = universe.validate_products(&product_registry) {
+        panic!("The products used by config {} and universe {} are not valid: {:?}", config.config_name(), config.universe_name(), errors);
+    }
+    universe
 }
 
 #[cfg(test)]
@@ -121,8 +228,13 @@ mod tests_int {
     use std::path::Path;
 
     use crate::MainConfig;
+    use crate::contracts::OfPostContract;
+    use crate::credits::WalletId;
+    use crate::products::Product;
+    use crate::sector::SectorPosition;
     use crate::save_load::{load_or_create_universe, load_time, load_universe};
     use crate::time::TimeStackState;
+    use crate::recipes::read_recipe_catalog;
     use crate::universe_generator::generate_simple_universe;
 
     #[test]
@@ -138,12 +250,18 @@ mod tests_int {
 
     #[test]
     fn save_load_universe() {
-        let universe = generate_simple_universe("save_load_universe".to_string());
+        let mut universe = generate_simple_universe("save_load_universe".to_string(), &read_recipe_catalog("default"));
+        universe.credits.grant(0, &WalletId::Construct("The_base_1".to_string()), 100);
+        universe.post_contract(OfPostContract::new("The_base_1".to_string(), Product::ware("Ores"), 10, 5, 40, 0)).unwrap();
         universe.save();
         let loaded_universe = load_universe(universe.universe_name().to_string());
         assert_eq!(universe.universe_name(), loaded_universe.universe_name());
         assert_eq!(universe.time(), loaded_universe.time());
         assert_eq!(universe.constructs(), loaded_universe.constructs());
+        assert_eq!(universe.credits(), loaded_universe.credits());
+        assert_eq!(universe.contract_board(), loaded_universe.contract_board());
+        assert_eq!(universe.sectors(), loaded_universe.sectors());
+        assert!(loaded_universe.sectors().get(&SectorPosition::new(1, 1, 1)).unwrap().is_linked_to(&SectorPosition::new(2, 2, 2)));
 
         //Cleanup
         fs::remove_dir_all("./save/save_load_universe/").expect("Had trouble cleanup after save_load_time");
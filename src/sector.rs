@@ -1,5 +1,10 @@
+use std::cmp::min;
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::my_little_universe::{MyLittleUniverse, MyLittleUniverseReturnValues};
+use crate::products::Product;
 use crate::sector::SectorEvenReturnType::{Approved, Denied, Entered, SectorState};
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -32,6 +37,51 @@ pub enum SectorEvenReturnType {
 pub struct Sector {
     groups: Vec<Vec<String>>,
     position: SectorPosition,
+    #[serde(default)]
+    deposits: Vec<ResourceDeposit>,
+    // Jump gates to the neighbouring sectors, a link is always added to both sectors.
+    #[serde(default)]
+    links: Vec<SectorPosition>,
+}
+
+// An asteroid field or similar in a group of the sector, it regenerates up to its capacity when it has a regeneration.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ResourceDeposit {
+    group_address: usize,
+    product: Product,
+    amount: u32,
+    #[serde(default)]
+    regeneration_per_turn: u32,
+    #[serde(default)]
+    capacity: u32,
+}
+
+impl ResourceDeposit {
+    pub fn new(group_address: usize, product: Product, amount: u32) -> Self {
+        ResourceDeposit { group_address, product, amount, regeneration_per_turn: 0, capacity: amount }
+    }
+
+    pub fn with_regeneration(mut self, regeneration_per_turn: u32, capacity: u32) -> Self {
+        self.regeneration_per_turn = regeneration_per_turn;
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn group_address(&self) -> usize {
+        self.group_address
+    }
+    pub fn product(&self) -> &Product {
+        &self.product
+    }
+    pub fn amount(&self) -> u32 {
+        self.amount
+    }
+    pub fn regeneration_per_turn(&self) -> u32 {
+        self.regeneration_per_turn
+    }
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Hash, std::cmp::Eq)]
@@ -45,15 +95,67 @@ impl SectorPosition {
     pub fn new(x: u8, y: u8, z: u8) -> Self {
         SectorPosition { x, y, z }
     }
+
+    // Diagonal neighbours are one sector away, like the straight ones.
+    pub fn distance_to(&self, other: &SectorPosition) -> u32 {
+        [self.x.abs_diff(other.x), self.y.abs_diff(other.y), self.z.abs_diff(other.z)].into_iter().max().unwrap() as u32
+    }
 }
 
 impl Sector {
     pub fn new(groups: Vec<Vec<String>>, position: SectorPosition) -> Self {
-        Sector { groups, position }
+        Sector { groups, position, deposits: Vec::new(), links: Vec::new() }
     }
     pub fn groups(&self) -> &Vec<Vec<String>> {
         &self.groups
     }
+    pub fn position(&self) -> &SectorPosition {
+        &self.position
+    }
+    pub fn links(&self) -> &Vec<SectorPosition> {
+        &self.links
+    }
+
+    pub fn is_linked_to(&self, sector_position: &SectorPosition) -> bool {
+        self.links.contains(sector_position)
+    }
+
+    pub fn add_link(&mut self, sector_position: SectorPosition) {
+        if !self.is_linked_to(&sector_position) {
+            self.links.push(sector_position);
+        }
+    }
+
+    pub fn remove_link(&mut self, sector_position: &SectorPosition) {
+        self.links.retain(|link| link.ne(sector_position));
+    }
+    pub fn deposits(&self) -> &Vec<ResourceDeposit> {
+        &self.deposits
+    }
+
+    pub fn add_deposit(&mut self, deposit: ResourceDeposit) {
+        self.deposits.push(deposit);
+    }
+
+    // Takes from the deposits of the group in the order they were added, returns what was extracted.
+    pub fn extract(&mut self, group_address: usize, product: &Product, amount: u32) -> u32 {
+        let mut extracted = 0;
+        for deposit in self.deposits.iter_mut()
+            .filter(|deposit| deposit.group_address == group_address && deposit.product.eq(product)) {
+            let taken = min(deposit.amount, amount - extracted);
+            deposit.amount -= taken;
+            extracted += taken;
+        }
+        extracted
+    }
+
+    pub fn regenerate_deposits(&mut self) {
+        for deposit in &mut self.deposits {
+            if deposit.amount < deposit.capacity {
+                deposit.amount = min(deposit.capacity, deposit.amount + deposit.regeneration_per_turn);
+            }
+        }
+    }
 
     pub fn push_event(&mut self, event: &SectorEventType) -> SectorEvenReturnType {
         // self.event_stack.push(event.clone());
@@ -140,9 +242,97 @@ impl Sector {
     }
 }
 
+impl MyLittleUniverse {
+    pub fn sectors(&self) -> &HashMap<SectorPosition, Sector> {
+        &self.sectors
+    }
+
+    pub(crate) fn add_sector_link(&mut self, from: SectorPosition, to: SectorPosition) -> MyLittleUniverseReturnValues {
+        if let Err(return_value) = self.validate_sector_link(&from, &to) {
+            return return_value;
+        }
+        if self.sectors.get(&from).unwrap().is_linked_to(&to) {
+            return MyLittleUniverseReturnValues::Denied(format!("Sector {:?} is already linked to {:?}.", from, to));
+        }
+        self.sectors.get_mut(&from).unwrap().add_link(to.clone());
+        self.sectors.get_mut(&to).unwrap().add_link(from);
+        MyLittleUniverseReturnValues::SectorLinkAdded
+    }
+
+    // Constructs already in transit over the link still arrive.
+    pub(crate) fn remove_sector_link(&mut self, from: SectorPosition, to: SectorPosition) -> MyLittleUniverseReturnValues {
+        if let Err(return_value) = self.validate_sector_link(&from, &to) {
+            return return_value;
+        }
+        if !self.sectors.get(&from).unwrap().is_linked_to(&to) {
+            return MyLittleUniverseReturnValues::Denied(format!("Sector {:?} is not linked to {:?}.", from, to));
+        }
+        self.sectors.get_mut(&from).unwrap().remove_link(&to);
+        self.sectors.get_mut(&to).unwrap().remove_link(&from);
+        MyLittleUniverseReturnValues::SectorLinkRemoved
+    }
+
+    fn validate_sector_link(&self, from: &SectorPosition, to: &SectorPosition) -> Result<(), MyLittleUniverseReturnValues> {
+        if from.eq(to) {
+            return Err(MyLittleUniverseReturnValues::Denied(format!("Sector {:?} cannot be linked to itself.", from)));
+        }
+        for sector_position in [from, to] {
+            if !self.sectors.contains_key(sector_position) {
+                return Err(MyLittleUniverseReturnValues::CouldNotFindSector(sector_position.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests_int {
+    use crate::{ExternalCommandReturnValues, ExternalCommands};
+    use crate::my_little_universe::{ExternalUniverseEventType, MyLittleUniverseReturnValues, OfMove};
+    use crate::recipes::read_recipe_catalog;
     use crate::sector::{ExternalSectorEventType, InternalSectorEventType, Sector, SectorEvenReturnType, SectorEventType, SectorPosition};
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn moves_follow_the_jump_gates() {
+        let mut universe = generate_simple_universe("jump_gates".to_string(), &read_recipe_catalog("default"));
+        let sector_1 = SectorPosition::new(1, 1, 1);
+        let sector_2 = SectorPosition::new(2, 2, 2);
+        let link = |universe: &mut crate::my_little_universe::MyLittleUniverse, add: bool, from: &SectorPosition, to: &SectorPosition| {
+            let event = if add {
+                ExternalUniverseEventType::AddSectorLink { from: from.clone(), to: to.clone() }
+            } else {
+                ExternalUniverseEventType::RemoveSectorLink { from: from.clone(), to: to.clone() }
+            };
+            universe.handle_event(ExternalCommands::Universe(event))
+        };
+        let move_transport = |universe: &mut crate::my_little_universe::MyLittleUniverse| {
+            universe.handle_event(ExternalCommands::Universe(ExternalUniverseEventType::Move(OfMove::new("transport".to_string(), SectorPosition::new(2, 2, 2), None))))
+        };
+
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::SectorLinkRemoved), link(&mut universe, false, &sector_2, &sector_1));
+        assert!(universe.sectors().values().all(|sector| sector.links().is_empty()));
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotMoveToSector("No jump gate from SectorPosition { x: 1, y: 1, z: 1 } to SectorPosition { x: 2, y: 2, z: 2 }".to_string())),
+            move_transport(&mut universe)
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Sector SectorPosition { x: 1, y: 1, z: 1 } is not linked to SectorPosition { x: 2, y: 2, z: 2 }.".to_string())),
+            link(&mut universe, false, &sector_1, &sector_2)
+        );
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::CouldNotFindSector(SectorPosition::new(3, 3, 3))),
+            link(&mut universe, true, &sector_1, &SectorPosition::new(3, 3, 3))
+        );
+
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::SectorLinkAdded), link(&mut universe, true, &sector_1, &sector_2));
+        assert_eq!(&vec![sector_1.clone()], universe.sectors().get(&sector_2).unwrap().links());
+        assert_eq!(
+            ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::Denied("Sector SectorPosition { x: 2, y: 2, z: 2 } is already linked to SectorPosition { x: 1, y: 1, z: 1 }.".to_string())),
+            link(&mut universe, true, &sector_2, &sector_1)
+        );
+        assert_eq!(ExternalCommandReturnValues::Universe(MyLittleUniverseReturnValues::InTransit(1)), move_transport(&mut universe));
+    }
 
     #[test]
     fn one_construct() {
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::ExternalCommands;
+use crate::construct_module::ConstructModuleType;
+use crate::my_little_universe::MyLittleUniverse;
+use crate::products::Product;
+use crate::time::{ExternalTimeEventType, ManualClock, TimeClock};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SimulationSummary {
+    first_turn: u64,
+    last_turn: u64,
+    elapsed: Duration,
+    produced: HashMap<Product, u64>,
+    consumed: HashMap<Product, u64>,
+    storage_totals: HashMap<Product, u64>,
+}
+
+impl SimulationSummary {
+    pub fn turns(&self) -> u64 {
+        self.last_turn - self.first_turn
+    }
+    pub fn produced(&self) -> &HashMap<Product, u64> {
+        &self.produced
+    }
+    pub fn consumed(&self) -> &HashMap<Product, u64> {
+        &self.consumed
+    }
+    pub fn storage_totals(&self) -> &HashMap<Product, u64> {
+        &self.storage_totals
+    }
+
+    fn per_turn(&self, amount: u64) -> f64 {
+        if self.turns() == 0 {
+            0.0
+        } else {
+            amount as f64 / self.turns() as f64
+        }
+    }
+}
+
+impl Display for SimulationSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Simulated {} turns (turn {} -> {}) in {:.2?} ({:.1} turns/s).",
+                 self.turns(),
+                 self.first_turn,
+                 self.last_turn,
+                 self.elapsed,
+                 self.turns() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON))?;
+
+        writeln!(f, "Production throughput per turn:")?;
+        let mut products: Vec<&Product> = self.produced.keys().chain(self.consumed.keys()).collect();
+        products.sort_by_key(|product| product.to_string());
+        products.dedup();
+        for product in products {
+            writeln!(f, "  {}: produced {:.2}, consumed {:.2}",
+                     product,
+                     self.per_turn(*self.produced.get(product).unwrap_or(&0)),
+                     self.per_turn(*self.consumed.get(product).unwrap_or(&0)))?;
+        }
+
+        writeln!(f, "Storage totals:")?;
+        let mut storage_totals: Vec<(&Product, &u64)> = self.storage_totals.iter().collect();
+        storage_totals.sort_by_key(|(product, _)| product.to_string());
+        for (product, amount) in storage_totals {
+            writeln!(f, "  {}: {}", product, amount)?;
+        }
+        Ok(())
+    }
+}
+
+// Runs the given amount of turns as fast as possible. The universe gets a manual clock that is
+// moved exactly one turn duration per turn, so the configured speed never throttles the run.
+pub fn fast_forward(universe: &mut MyLittleUniverse, turns: u64) -> SimulationSummary {
+    let started = Instant::now();
+    let clock = Arc::new(ManualClock::new(0));
+    universe.set_clock(TimeClock::new(clock.clone()));
+
+    let first_turn = universe.time().turn();
+    let target_turn = first_turn + turns;
+    universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::StartUntilTurn(target_turn)));
+
+    let (produced_before, consumed_before) = module_totals(universe);
+    while universe.time().turn() < target_turn {
+        clock.advance(universe.time().turn_min_duration_in_milli_secs());
+        if !universe.request_execute_turn() {
+            println!("Time stopped at turn {} before reaching turn {}.", universe.time().turn(), target_turn);
+            break;
+        }
+    }
+    let (produced, consumed) = module_totals(universe);
+
+    SimulationSummary {
+        first_turn,
+        last_turn: universe.time().turn(),
+        elapsed: started.elapsed(),
+        produced: subtract_totals(produced, &produced_before),
+        consumed: subtract_totals(consumed, &consumed_before),
+        storage_totals: storage_totals(universe),
+    }
+}
+
+// What the modules made and used so far, cargo that is only moved between constructs is not counted.
+// Generator fuel and shipyard materials are not part of the production chains, so they are left out.
+fn module_totals(universe: &MyLittleUniverse) -> (HashMap<Product, u64>, HashMap<Product, u64>) {
+    let mut produced = HashMap::new();
+    let mut consumed = HashMap::new();
+    for construct in universe.constructs().values() {
+        for module in construct.modules() {
+            match module {
+                ConstructModuleType::Production(production_module) => {
+                    for amount in production_module.stats().produced() {
+                        *produced.entry(amount.product().clone()).or_insert(0) += u64::from(amount.amount());
+                    }
+                    for amount in production_module.stats().consumed() {
+                        *consumed.entry(amount.product().clone()).or_insert(0) += u64::from(amount.amount());
+                    }
+                }
+                ConstructModuleType::Mining(mining_module) => {
+                    *produced.entry(mining_module.product()).or_insert(0) += mining_module.mined();
+                }
+                _ => {}
+            }
+        }
+    }
+    (produced, consumed)
+}
+
+fn subtract_totals(mut totals: HashMap<Product, u64>, before: &HashMap<Product, u64>) -> HashMap<Product, u64> {
+    for (product, amount) in totals.iter_mut() {
+        *amount = amount.saturating_sub(*before.get(product).unwrap_or(&0));
+    }
+    totals.retain(|_, amount| *amount > 0);
+    totals
+}
+
+fn storage_totals(universe: &MyLittleUniverse) -> HashMap<Product, u64> {
+    let mut storage_totals = HashMap::new();
+    for construct in universe.constructs().values() {
+        for (product, amount) in construct.current_storage() {
+            *storage_totals.entry(product.clone()).or_insert(0) += u64::from(*amount);
+        }
+    }
+    storage_totals
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEventType, InternalConstructEventType};
+    use crate::construct::production_module::ProductionModule;
+    use crate::construct_module::ConstructModuleType::Production;
+    use crate::ExternalCommands;
+    use crate::products::Product;
+    use crate::simulation::fast_forward;
+    use crate::time::ExternalTimeEventType;
+    use crate::recipes::read_recipe_catalog;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn fast_forward_ignores_speed() {
+        let mut universe = generate_simple_universe("fast_forward".to_string(), &read_recipe_catalog("default"));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::SetSpeed(60 * 60 * 1000)));
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 10))));
+
+        let summary = fast_forward(&mut universe, 20);
+
+        assert_eq!(20, summary.turns());
+        assert_eq!(20, universe.time().turn());
+        assert!(universe.time().paused());
+        assert_eq!(Some(&10), summary.consumed().get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&20), summary.produced().get(&Product::ware("Ores")));
+        assert_eq!(None, summary.storage_totals().get(&Product::ware("PowerCells")));
+        assert_eq!(Some(&20), summary.storage_totals().get(&Product::ware("Ores")));
+    }
+
+    #[test]
+    fn throughput_counts_what_the_modules_made() {
+        let recipe_catalog = read_recipe_catalog("default");
+        let mut universe = generate_simple_universe("throughput".to_string(), &recipe_catalog);
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        assert_eq!(Ok(()), base.install(Production(ProductionModule::from_recipe("OreToPower".to_string(), recipe_catalog.get("OreToPower").unwrap(), 0))));
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 10))));
+        // Cargo that is only loaded is not production.
+        universe.constructs.get_mut("transport").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 7))));
+
+        let summary = fast_forward(&mut universe, 10);
+
+        // The ores made and used by the same base show up on both sides, the last batch of ores is still running.
+        assert_eq!(Some(&18), summary.produced().get(&Product::ware("Ores")));
+        assert_eq!(Some(&9), summary.consumed().get(&Product::ware("Ores")));
+        assert_eq!(Some(&16), summary.produced().get(&Product::ware("Metals")));
+        assert_eq!(None, summary.consumed().get(&Product::ware("Metals")));
+        assert_eq!(Some(&23), summary.storage_totals().get(&Product::ware("Metals")));
+    }
+}
@@ -1,7 +1,86 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
+use crate::breakpoint::Breakpoint;
+
+pub trait Clock: Send + Sync {
+    fn epoch_time_in_milli_secs(&self) -> u64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn epoch_time_in_milli_secs(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis()
+            .try_into()
+            .unwrap()
+    }
+}
+
+// Only moves when told to, so turn durations can be tested without sleeping.
+pub struct ManualClock {
+    epoch_time_in_milli_secs: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new(epoch_time_in_milli_secs: u64) -> Self {
+        ManualClock { epoch_time_in_milli_secs: AtomicU64::new(epoch_time_in_milli_secs) }
+    }
+
+    pub fn advance(&self, milli_secs: u64) {
+        self.epoch_time_in_milli_secs.fetch_add(milli_secs, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn epoch_time_in_milli_secs(&self) -> u64 {
+        self.epoch_time_in_milli_secs.load(Ordering::SeqCst)
+    }
+}
+
+// The clock is not part of the time state, so it is neither saved nor compared.
+#[derive(Clone)]
+pub struct TimeClock(Arc<dyn Clock>);
+
+impl TimeClock {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        TimeClock(clock)
+    }
+
+    pub fn system() -> Self {
+        TimeClock(Arc::new(SystemClock))
+    }
+
+    pub fn epoch_time_in_milli_secs(&self) -> u64 {
+        self.0.epoch_time_in_milli_secs()
+    }
+}
+
+impl Default for TimeClock {
+    fn default() -> Self {
+        TimeClock::system()
+    }
+}
+
+impl PartialEq for TimeClock {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Debug for TimeClock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TimeClock")
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum TimeEventType {
     Internal(InternalTimeEventType),
@@ -12,6 +91,7 @@ pub enum TimeEventType {
 pub enum InternalTimeEventType {
     ReadyForNextTurn,
     StartedNextTurn,
+    BreakpointsFired(Vec<String>),
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -21,6 +101,8 @@ pub enum ExternalTimeEventType {
     StartUntilTurn(u64),
     SetSpeed(u64),
     GetTimeStackState { include_stack: bool },
+    AddBreakpoint(Breakpoint),
+    RemoveBreakpoint(String),
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -33,29 +115,49 @@ pub struct TimeStackState {
     paused: bool,
     ready_for_next_turn: bool,
     event_stack: Vec<TimeEventType>,
+    #[serde(default)]
+    breakpoints: Vec<Breakpoint>,
+    #[serde(default)]
+    fired_breakpoints: Vec<String>,
+    #[serde(skip)]
+    clock: TimeClock,
+}
+
+impl Default for TimeStackState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TimeStackState {
     pub fn new() -> Self {
+        Self::new_with_clock(TimeClock::system())
+    }
+
+    pub fn new_with_clock(clock: TimeClock) -> Self {
         TimeStackState {
             turn: 0,
             turn_min_duration_in_milli_secs: 0,
-            last_turn_timestamp: Self::epcoh_time(),
+            last_turn_timestamp: clock.epoch_time_in_milli_secs(),
             last_processed_event_index: 0,
             paused: true,
             ready_for_next_turn: true,
             event_stack: Vec::new(),
             pause_at_turn: Option::None,
+            breakpoints: Vec::new(),
+            fired_breakpoints: Vec::new(),
+            clock,
         }
     }
 
-    fn epcoh_time() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis()
-            .try_into()
-            .unwrap()
+    pub fn set_clock(&mut self, clock: TimeClock) {
+        // The last turn timestamp was taken from the old clock, so start counting from now on the new one.
+        self.last_turn_timestamp = clock.epoch_time_in_milli_secs();
+        self.clock = clock;
+    }
+
+    fn epcoh_time(&self) -> u64 {
+        self.clock.epoch_time_in_milli_secs()
     }
 
     pub fn push_event(&mut self, event: &TimeEventType) -> TimeEventReturnType {
@@ -75,6 +177,11 @@ impl TimeStackState {
                         self.next_turn();
                         TimeEventReturnType::Received
                     }
+                    InternalTimeEventType::BreakpointsFired(breakpoint_names) => {
+                        self.paused = true;
+                        self.fired_breakpoints = breakpoint_names.clone();
+                        TimeEventReturnType::Received
+                    }
                 }
             }
 
@@ -86,6 +193,7 @@ impl TimeStackState {
                     }
                     ExternalTimeEventType::Start => {
                         self.paused = false;
+                        self.fired_breakpoints = Vec::new();
                         TimeEventReturnType::Received
                     }
                     ExternalTimeEventType::SetSpeed(turn_min_duration_in_milli_secs) => {
@@ -104,6 +212,21 @@ impl TimeStackState {
                     ExternalTimeEventType::StartUntilTurn(turn) => {
                         self.pause_at_turn = Option::Some(*turn);
                         self.paused = false;
+                        self.fired_breakpoints = Vec::new();
+                        TimeEventReturnType::Received
+                    }
+                    ExternalTimeEventType::AddBreakpoint(breakpoint) => {
+                        if self.breakpoints.iter().any(|b| b.name().eq(breakpoint.name())) {
+                            return TimeEventReturnType::Denied(format!("Breakpoint with name {} already exists.", breakpoint.name()));
+                        }
+                        self.breakpoints.push(breakpoint.clone());
+                        TimeEventReturnType::Received
+                    }
+                    ExternalTimeEventType::RemoveBreakpoint(breakpoint_name) => {
+                        if !self.breakpoints.iter().any(|b| b.name().eq(breakpoint_name)) {
+                            return TimeEventReturnType::Denied(format!("Breakpoint with name {} does not exist.", breakpoint_name));
+                        }
+                        self.breakpoints.retain(|b| b.name().ne(breakpoint_name));
                         TimeEventReturnType::Received
                     }
                 }
@@ -113,7 +236,7 @@ impl TimeStackState {
 
     pub fn request_execute_turn(&mut self) -> bool {
         if self.ready_for_next_turn() && !self.paused() {
-            let now = Self::epcoh_time();
+            let now = self.epcoh_time();
             let min_instant_where_we_can_switch_turn = self.last_turn_timestamp().checked_add(self.turn_min_duration_in_milli_secs() as u64).unwrap();
 
             if now >= min_instant_where_we_can_switch_turn {
@@ -124,10 +247,19 @@ impl TimeStackState {
         false
     }
 
+    // None when no turn can start before an event changes the time state.
+    pub fn milli_secs_until_next_turn(&self) -> Option<u64> {
+        if !self.ready_for_next_turn() || self.paused() {
+            return None;
+        }
+        let min_instant_where_we_can_switch_turn = self.last_turn_timestamp().saturating_add(self.turn_min_duration_in_milli_secs());
+        Some(min_instant_where_we_can_switch_turn.saturating_sub(self.epcoh_time()))
+    }
+
     fn next_turn(&mut self) {
         self.turn += 1;
         self.ready_for_next_turn = false;
-        self.last_turn_timestamp = Self::epcoh_time();
+        self.last_turn_timestamp = self.epcoh_time();
 
         if let Some(pause_at_turn) = self.pause_at_turn {
             if self.turn >= pause_at_turn {
@@ -151,16 +283,39 @@ impl TimeStackState {
     pub fn ready_for_next_turn(&self) -> bool {
         self.ready_for_next_turn
     }
+    pub fn breakpoints(&self) -> &Vec<Breakpoint> {
+        &self.breakpoints
+    }
+    pub fn fired_breakpoints(&self) -> &Vec<String> {
+        &self.fired_breakpoints
+    }
+
+    // conditions_met is in the same order as breakpoints; returns the names of the breakpoints that fired.
+    pub fn update_breakpoints(&mut self, conditions_met: Vec<bool>) -> Vec<String> {
+        self.breakpoints.iter_mut()
+            .zip(conditions_met)
+            .filter_map(|(breakpoint, condition_met)| {
+                if breakpoint.update_condition_met(condition_met) {
+                    Some(breakpoint.name().to_string())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum TimeEventReturnType {
     StackState(TimeStackState),
     Received,
+    Denied(String),
 }
 
 #[cfg(test)]
 mod tests_int {
+    use std::sync::Arc;
+
     use serde_json::json;
 
     use crate::time::*;
@@ -199,6 +354,38 @@ mod tests_int {
         assert_eq!(1, time_state.turn);
     }
 
+    #[test]
+    fn turn_duration_follows_clock() {
+        let clock = Arc::new(ManualClock::new(0));
+        let mut time_state = TimeStackState::new_with_clock(TimeClock::new(clock.clone()));
+        time_state.push_event(&TimeEventType::External(ExternalTimeEventType::SetSpeed(1000)));
+        time_state.push_event(&TimeEventType::External(ExternalTimeEventType::Start));
+
+        assert!(!time_state.request_execute_turn());
+        clock.advance(999);
+        assert!(!time_state.request_execute_turn());
+        clock.advance(1);
+        assert!(time_state.request_execute_turn());
+        assert_eq!(1, time_state.turn);
+
+        time_state.push_event(&TimeEventType::Internal(InternalTimeEventType::ReadyForNextTurn));
+        assert!(!time_state.request_execute_turn());
+        clock.advance(1000);
+        assert!(time_state.request_execute_turn());
+        assert_eq!(2, time_state.turn);
+
+        time_state.push_event(&TimeEventType::Internal(InternalTimeEventType::ReadyForNextTurn));
+        clock.advance(400);
+        assert_eq!(Some(600), time_state.milli_secs_until_next_turn());
+        time_state.push_event(&TimeEventType::External(ExternalTimeEventType::Pause));
+        assert_eq!(None, time_state.milli_secs_until_next_turn());
+        time_state.push_event(&TimeEventType::External(ExternalTimeEventType::Start));
+        time_state.push_event(&TimeEventType::External(ExternalTimeEventType::SetSpeed(0)));
+        assert_eq!(Some(0), time_state.milli_secs_until_next_turn());
+        assert!(time_state.request_execute_turn());
+        assert_eq!(3, time_state.turn);
+    }
+
     #[test]
     fn serialise_deserialize() {
         let mut time_state = TimeStackState::new();
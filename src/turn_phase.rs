@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::construct_module::CanHandleNextTurn;
+use crate::my_little_universe::MyLittleUniverse;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum TurnPhase {
+    PreTurn,
+    Production,
+    Logistics,
+    Movement,
+    PostTurn,
+}
+
+impl TurnPhase {
+    pub const IN_ORDER: [TurnPhase; 5] = [TurnPhase::PreTurn, TurnPhase::Production, TurnPhase::Logistics, TurnPhase::Movement, TurnPhase::PostTurn];
+}
+
+pub type TurnPhaseHandler = fn(&mut MyLittleUniverse, &u64);
+
+// Handlers of the same phase are executed in the order they were registered.
+pub struct TurnPipeline {
+    handlers: Vec<(TurnPhase, TurnPhaseHandler)>,
+}
+
+impl Default for TurnPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TurnPipeline {
+    pub fn new() -> Self {
+        let mut pipeline = TurnPipeline { handlers: Vec::new() };
+        pipeline.register(TurnPhase::Production, MyLittleUniverse::power_phase);
+        pipeline.register(TurnPhase::Production, MyLittleUniverse::mining_phase);
+        pipeline.register(TurnPhase::Production, production_phase);
+        pipeline.register(TurnPhase::Production, MyLittleUniverse::wear_phase);
+        pipeline.register(TurnPhase::Production, MyLittleUniverse::launch_finished_constructs);
+        pipeline.register(TurnPhase::Logistics, MyLittleUniverse::match_market_orders);
+        pipeline.register(TurnPhase::Movement, MyLittleUniverse::arrive_constructs);
+        pipeline.register(TurnPhase::PostTurn, MyLittleUniverse::evaluate_contracts);
+        pipeline.register(TurnPhase::PostTurn, MyLittleUniverse::record_prices);
+        pipeline.register(TurnPhase::PostTurn, MyLittleUniverse::evaluate_breakpoints);
+        pipeline
+    }
+
+    pub fn register(&mut self, phase: TurnPhase, handler: TurnPhaseHandler) {
+        self.handlers.push((phase, handler));
+    }
+
+    pub fn handlers(&self, phase: &TurnPhase) -> Vec<TurnPhaseHandler> {
+        self.handlers.iter()
+            .filter(|(handler_phase, _)| handler_phase.eq(phase))
+            .map(|(_, handler)| *handler)
+            .collect()
+    }
+}
+
+// The constructs are driven through their CanHandleNextTurn hook.
+fn production_phase(universe: &mut MyLittleUniverse, current_turn: &u64) {
+    for construct_name in universe.construct_names_in_turn_order() {
+        universe.constructs.get_mut(&construct_name).unwrap().next_turn(current_turn);
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEventType, InternalConstructEventType};
+    use crate::ExternalCommands;
+    use crate::my_little_universe::MyLittleUniverse;
+    use crate::products::Product;
+    use crate::time::ExternalTimeEventType;
+    use crate::turn_phase::TurnPhase;
+    use crate::recipes::read_recipe_catalog;
+    use crate::universe_generator::generate_simple_universe;
+
+    #[test]
+    fn phases_run_in_order() {
+        let mut universe = generate_simple_universe("phases_run_in_order".to_string(), &read_recipe_catalog("default"));
+        universe.register_turn_phase_handler(TurnPhase::PostTurn, unload_all_ores);
+        universe.register_turn_phase_handler(TurnPhase::PreTurn, load_one_power_cell);
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        // The power cell loaded before production is consumed in the same turn.
+        assert!(universe.request_execute_turn());
+        assert_eq!(None, universe.constructs().get("The_base_1").unwrap().current_storage().get(&Product::ware("PowerCells")));
+
+        // The ores produced are removed again after the turn.
+        assert!(universe.request_execute_turn());
+        assert_eq!(None, universe.constructs().get("The_base_1").unwrap().current_storage().get(&Product::ware("PowerCells")));
+        assert_eq!(None, universe.constructs().get("The_base_1").unwrap().current_storage().get(&Product::ware("Ores")));
+    }
+
+    #[test]
+    fn constructs_are_in_name_order() {
+        let universe = generate_simple_universe("constructs_are_in_name_order".to_string(), &read_recipe_catalog("default"));
+        assert_eq!(
+            vec!["The_base_1".to_string(), "The_base_2".to_string(), "transport".to_string()],
+            universe.construct_names_in_turn_order()
+        );
+    }
+
+    fn load_one_power_cell(universe: &mut MyLittleUniverse, _current_turn: &u64) {
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 1))));
+    }
+
+    fn unload_all_ores(universe: &mut MyLittleUniverse, _current_turn: &u64) {
+        universe.constructs.get_mut("The_base_1").unwrap()
+            .push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestUnload(Amount::new(Product::ware("Ores"), u32::MAX))));
+    }
+}
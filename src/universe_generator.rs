@@ -11,8 +11,9 @@ use crate::construct::production_module::ProductionModule;
 use crate::construct_module::ConstructModuleType::Production as ProductionModuleType;
 use crate::MainConfig;
 use crate::my_little_universe::MyLittleUniverse;
+use crate::recipes::RecipeCatalog;
 use crate::products::Product;
-use crate::sector::{Sector, SectorPosition};
+use crate::sector::{ResourceDeposit, Sector, SectorPosition};
 use crate::time::TimeStackState;
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -20,13 +21,13 @@ pub struct UniverseGeneratorConfig {
     method: String,
 }
 
-pub fn generate_universe(config: &MainConfig) -> MyLittleUniverse {
+pub fn generate_universe(config: &MainConfig, recipe_catalog: &RecipeCatalog) -> MyLittleUniverse {
     let generator_config = read_universe_generator_config_file(&config.config_name().to_string());
 
     if generator_config.method.eq("generate_performance_test_universe") {
-        generate_performance_test_universe(config.universe_name.clone())
+        generate_performance_test_universe(config.universe_name.clone(), recipe_catalog)
     } else if generator_config.method.eq("generate_simple_universe") {
-        generate_simple_universe(config.universe_name.clone())
+        generate_simple_universe(config.universe_name.clone(), recipe_catalog)
     } else {
         panic!("Does not know the generate universe method, got {}", generator_config.method)
     }
@@ -46,25 +47,21 @@ fn read_universe_generator_config_file(config_name: &String) -> UniverseGenerato
         .expect("Something went wrong parsing the file from")
 }
 
-pub fn generate_simple_universe(universe_name: String) -> MyLittleUniverse {
+pub fn generate_simple_universe(universe_name: String, recipe_catalog: &RecipeCatalog) -> MyLittleUniverse {
     let sector_position_1 = ConstructPositionSector::new(SectorPosition::new(1, 1, 1), 0);
     let mut construct_1 = Construct::new("The_base_1".to_string(), 500, sector_position_1.clone());
-    assert_eq!(Ok(()), construct_1.install(ProductionModuleType(ProductionModule::new(
+    assert_eq!(Ok(()), construct_1.install(ProductionModuleType(ProductionModule::from_recipe(
         "PowerToOre".to_string(),
-        vec![Amount::new(Product::PowerCells, 1)],
-        vec![Amount::new(Product::Ores, 2)],
-        1,
+        recipe_catalog.get("PowerToOre").expect("The simple universe needs the recipe PowerToOre"),
         0,
     ))));
     construct_1.position.install();
 
     let sector_position_2 = ConstructPositionSector::new(SectorPosition::new(2, 2, 2), 0);
     let mut construct_2 = Construct::new("The_base_2".to_string(), 500, sector_position_2.clone());
-    assert_eq!(Ok(()), construct_2.install(ProductionModuleType(ProductionModule::new(
+    assert_eq!(Ok(()), construct_2.install(ProductionModuleType(ProductionModule::from_recipe(
         "OreToPower".to_string(),
-        vec![Amount::new(Product::Ores, 1)],
-        vec![Amount::new(Product::Metals, 2)],
-        1,
+        recipe_catalog.get("OreToPower").expect("The simple universe needs the recipe OreToPower"),
         0,
     ))));
     construct_2.position.install();
@@ -79,8 +76,11 @@ pub fn generate_simple_universe(universe_name: String) -> MyLittleUniverse {
     let mut sector_1 = Sector::new(Vec::new(), sector_position_1.sector_position().clone());
     sector_1.enter_sector("The_base_1".to_string(), Some(0));
     sector_1.enter_sector("transport".to_string(), Some(0));
+    sector_1.add_deposit(ResourceDeposit::new(0, Product::ware("Ores"), 1000).with_regeneration(1, 1000));
     let mut sector_2 = Sector::new(Vec::new(), sector_position_2.sector_position().clone());
     sector_2.enter_sector("The_base_2".to_string(), Some(0));
+    sector_1.add_link(sector_position_2.sector_position().clone());
+    sector_2.add_link(sector_position_1.sector_position().clone());
 
     let mut sectors = HashMap::new();
     sectors.insert(sector_position_1.sector_position().clone(), sector_1);
@@ -90,7 +90,8 @@ pub fn generate_simple_universe(universe_name: String) -> MyLittleUniverse {
 }
 
 
-pub fn generate_performance_test_universe(universe_name: String) -> MyLittleUniverse {
+// Every group gets one construct per recipe in the catalog.
+pub fn generate_performance_test_universe(universe_name: String, recipe_catalog: &RecipeCatalog) -> MyLittleUniverse {
     let mut constructs: HashMap<String, Construct> = HashMap::new();
     let mut sectors = HashMap::new();
 
@@ -100,9 +101,16 @@ pub fn generate_performance_test_universe(universe_name: String) -> MyLittleUniv
             for z in 1..20 {
                 let sector_position = SectorPosition::new(x, y, z);
                 let mut sector = Sector::new(Vec::new(), sector_position.clone());
+                // Jump gates to the straight neighbours, each neighbour adds the way back itself.
+                for (dx, dy, dz) in [(-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)] {
+                    let (nx, ny, nz) = (x as i16 + dx, y as i16 + dy, z as i16 + dz);
+                    if [nx, ny, nz].iter().all(|coordinate| (1..20).contains(coordinate)) {
+                        sector.add_link(SectorPosition::new(nx as u8, ny as u8, nz as u8));
+                    }
+                }
                 for group_id in 1..50 {
-                    for production in [(Product::PowerCells, Product::Ores), (Product::Ores, Product::Metals), (Product::Metals, Product::PowerCells)] {
-                        let construct_name = format!("{}-{}-{}_{}:{:?}->{:?}", x, y, z, group_id, production.0, production.1);
+                    for recipe in recipe_catalog.recipes() {
+                        let construct_name = format!("{}-{}-{}_{}:{}", x, y, z, group_id, recipe.id());
                         let new_group_id = sector.enter_sector(construct_name.clone(), Some(group_id));
                         let sector_position = ConstructPositionSector::new(sector_position.clone(), new_group_id);
 
@@ -110,17 +118,13 @@ pub fn generate_performance_test_universe(universe_name: String) -> MyLittleUniv
                             construct_name,
                             500,
                             sector_position.clone());
-                        let production_module = ProductionModule::new(
-                            "production".to_string(),
-                            vec![Amount::new(production.0.clone(), 1)],
-                            vec![Amount::new(production.1, 2)],
-                            1,
-                            0,
-                        );
+                        let production_module = ProductionModule::from_recipe("production".to_string(), recipe, 0);
                         assert_eq!(Ok(()), construct.install(ProductionModuleType(production_module.clone())));
                         construct.position.install();
 
-                        construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(production.0, 200))));
+                        for input in recipe.input() {
+                            construct.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(input.product().clone(), 200))));
+                        }
 
                         constructs.insert(construct.name().to_string(), construct);
 
@@ -134,4 +138,16 @@ pub fn generate_performance_test_universe(universe_name: String) -> MyLittleUniv
 
     println!("Performance universe, generated {} constructs.", count_constructs);
     MyLittleUniverse::new(universe_name, TimeStackState::new(), constructs, sectors)
-}
\ No newline at end of file
+}
+
+// A sector four turns of travel away from the first sector of the simple universe, linked in both directions.
+#[cfg(test)]
+pub(crate) fn add_distant_sector(universe: &mut MyLittleUniverse) -> SectorPosition {
+    let distant = SectorPosition::new(5, 5, 5);
+    let home = SectorPosition::new(1, 1, 1);
+    let mut sector = Sector::new(Vec::new(), distant.clone());
+    sector.add_link(home.clone());
+    universe.sectors.insert(distant.clone(), sector);
+    universe.sectors.get_mut(&home).unwrap().add_link(distant.clone());
+    distant
+}
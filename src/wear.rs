@@ -0,0 +1,263 @@
+use std::cmp::min;
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::construct::amount::Amount;
+use crate::my_little_universe::MyLittleUniverse;
+
+const FULL_CONDITION: u32 = 100;
+
+// The condition of an installed module, the cycles are turned into wear by the wear phase.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub struct ModuleWear {
+    condition: u32,
+    unworn_cycles: u32,
+}
+
+impl Default for ModuleWear {
+    fn default() -> Self {
+        ModuleWear { condition: FULL_CONDITION, unworn_cycles: 0 }
+    }
+}
+
+impl ModuleWear {
+    pub fn condition(&self) -> u32 {
+        self.condition
+    }
+
+    pub fn is_broken_down(&self) -> bool {
+        self.condition == 0
+    }
+
+    // A worn module works at half speed just before it breaks down.
+    pub fn efficiency_percent(&self) -> u32 {
+        50 + self.condition / 2
+    }
+
+    pub fn record_cycle(&mut self) {
+        self.unworn_cycles += 1;
+    }
+
+    pub fn apply(&mut self, wear_per_cycle: u32) {
+        self.condition = self.condition.saturating_sub(self.unworn_cycles * wear_per_cycle);
+        self.unworn_cycles = 0;
+    }
+
+    pub fn repair(&mut self, condition: u32) {
+        self.condition = min(FULL_CONDITION, self.condition + condition);
+    }
+
+    pub fn needs_maintenance(&self) -> bool {
+        self.condition < FULL_CONDITION
+    }
+}
+
+// Storage modules have no cycles, so they do not wear.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum WearModuleType {
+    Production,
+    Generator,
+    Shipyard,
+    Mining,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct WearRate {
+    module_type: WearModuleType,
+    wear_per_cycle: u32,
+}
+
+impl WearRate {
+    pub fn new(module_type: WearModuleType, wear_per_cycle: u32) -> Self {
+        WearRate { module_type, wear_per_cycle }
+    }
+}
+
+// Every maintenance takes the cost from the construct storage and restores condition_per_maintenance, once per module per turn.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, Default)]
+pub struct WearCatalog {
+    rates: Vec<WearRate>,
+    maintenance_cost: Vec<Amount>,
+    condition_per_maintenance: u32,
+}
+
+impl WearCatalog {
+    pub fn new(rates: Vec<WearRate>, maintenance_cost: Vec<Amount>, condition_per_maintenance: u32) -> Self {
+        WearCatalog { rates, maintenance_cost, condition_per_maintenance }
+    }
+
+    pub fn wear_per_cycle(&self, module_type: &WearModuleType) -> u32 {
+        self.rates.iter()
+            .find(|rate| rate.module_type.eq(module_type))
+            .map_or(0, |rate| rate.wear_per_cycle)
+    }
+    pub fn maintenance_cost(&self) -> &Vec<Amount> {
+        &self.maintenance_cost
+    }
+    pub fn condition_per_maintenance(&self) -> u32 {
+        self.condition_per_maintenance
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let mut module_types = HashSet::new();
+
+        for rate in &self.rates {
+            if !module_types.insert(rate.module_type) {
+                errors.push(format!("Wear rate for {:?} modules is defined more than once.", rate.module_type));
+            }
+        }
+        for amount in &self.maintenance_cost {
+            if amount.amount() == 0 {
+                errors.push(format!("Maintenance has a cost of zero {}.", amount.product()));
+            }
+        }
+        if self.condition_per_maintenance == 0 {
+            errors.push("Maintenance needs a condition_per_maintenance above zero.".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub fn read_wear_catalog(config_name: &str) -> WearCatalog {
+    let config_folder = "./config/".to_string().add(config_name);
+
+    let wear_config_path = config_folder.to_string().add("/wear.json");
+    println!("Using wear config wear_config_path: {}", wear_config_path);
+
+    let wear_config = fs::read_to_string(&wear_config_path)
+        .unwrap_or_else(|_| panic!("Something went wrong reading the file {}", wear_config_path));
+
+    let wear_catalog: WearCatalog = serde_json::from_str(wear_config.as_str())
+        .unwrap_or_else(|_| panic!("Something went wrong parsing the file {}", wear_config_path));
+
+    if let Err(errors) = wear_catalog.validate() {
+        panic!("The wear in {} are not valid: {:?}", wear_config_path, errors);
+    }
+
+    wear_catalog
+}
+
+impl MyLittleUniverse {
+    // Runs after production, so the cycles of this turn already wear the modules.
+    pub(crate) fn wear_phase(&mut self, _current_turn: &u64) {
+        for construct_name in self.construct_names_in_turn_order() {
+            self.constructs.get_mut(&construct_name).unwrap().wear_and_maintain(&self.wear);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_int {
+    use crate::ExternalCommands;
+    use crate::construct::amount::Amount;
+    use crate::construct::construct::{ConstructEventType, ExternalConstructEventType, InternalConstructEventType};
+    use crate::construct::production_module::IdleReason;
+    use crate::products::Product;
+    use crate::recipes::read_recipe_catalog;
+    use crate::time::ExternalTimeEventType;
+    use crate::universe_generator::generate_simple_universe;
+    use crate::wear::{read_wear_catalog, WearCatalog, WearModuleType, WearRate};
+
+    #[test]
+    fn read_default_wear() {
+        let wear_catalog = read_wear_catalog("default");
+        // Wear is opt in, the shipped configs do not wear the modules.
+        assert_eq!(0, wear_catalog.wear_per_cycle(&WearModuleType::Production));
+        assert_eq!(&vec![Amount::new(Product::ware("Metals"), 1)], wear_catalog.maintenance_cost());
+
+        assert_eq!(Ok(()), read_wear_catalog("performance").validate());
+    }
+
+    #[test]
+    fn validate_wear() {
+        let wear_catalog = WearCatalog::new(vec![
+            WearRate::new(WearModuleType::Production, 1),
+            WearRate::new(WearModuleType::Production, 2),
+        ], vec![Amount::new(Product::ware("Metals"), 0)], 0);
+        assert_eq!(Err(vec![
+            "Wear rate for Production modules is defined more than once.".to_string(),
+            "Maintenance has a cost of zero Metals.".to_string(),
+            "Maintenance needs a condition_per_maintenance above zero.".to_string(),
+        ]), wear_catalog.validate());
+    }
+
+    #[test]
+    fn modules_wear_break_down_and_are_maintained() {
+        let mut universe = generate_simple_universe("wear".to_string(), &read_recipe_catalog("default"));
+        universe.set_wear(WearCatalog::new(vec![WearRate::new(WearModuleType::Production, 25)], vec![Amount::new(Product::ware("Metals"), 1)], 30));
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 20))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        // Every cycle takes one turn, until the worn module needs two turns for the cycle it starts on turn 5.
+        for _ in 0..5 {
+            assert!(universe.request_execute_turn());
+        }
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert_eq!(7, base.production_module("PowerToOre").unwrap().production_trigger_time());
+        assert_eq!(Some(&8), base.current_storage().get(&Product::ware("Ores")));
+        assert!(base.production_module("PowerToOre").unwrap().wear().is_broken_down());
+
+        // A broken down module does not finish its cycle, and stays down without metals.
+        for _ in 0..2 {
+            assert!(universe.request_execute_turn());
+        }
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert_eq!(Some(&8), base.current_storage().get(&Product::ware("Ores")));
+        assert_eq!(2, base.production_module("PowerToOre").unwrap().stats().idle_turns_for(&IdleReason::BrokenDown));
+
+        // Maintenance uses one metal each turn until the module is back in full condition.
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 10))));
+        base.push_event(&ConstructEventType::External(ExternalConstructEventType::SetModuleEnabled { module_name: "PowerToOre".to_string(), enabled: false }));
+        for _ in 0..4 {
+            assert!(universe.request_execute_turn());
+        }
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert_eq!(100, base.production_module("PowerToOre").unwrap().wear().condition());
+        assert_eq!(Some(&6), base.current_storage().get(&Product::ware("Metals")));
+
+        // Without maintenance the metals are left alone.
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        base.push_event(&ConstructEventType::External(ExternalConstructEventType::SetMaintenance(false)));
+        base.push_event(&ConstructEventType::External(ExternalConstructEventType::SetModuleEnabled { module_name: "PowerToOre".to_string(), enabled: true }));
+        for _ in 0..4 {
+            assert!(universe.request_execute_turn());
+        }
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert!(base.production_module("PowerToOre").unwrap().wear().condition() < 100);
+        assert_eq!(Some(&6), base.current_storage().get(&Product::ware("Metals")));
+    }
+
+    #[test]
+    fn default_universe_does_not_wear_out() {
+        let mut universe = generate_simple_universe("wear".to_string(), &read_recipe_catalog("default"));
+        universe.set_wear(read_wear_catalog("default"));
+        let base = universe.constructs.get_mut("The_base_1").unwrap();
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("PowerCells"), 100))));
+        base.push_event(&ConstructEventType::Internal(InternalConstructEventType::RequestLoad(Amount::new(Product::ware("Metals"), 10))));
+        universe.handle_event(ExternalCommands::Time(ExternalTimeEventType::Start));
+
+        for _ in 0..1000 {
+            assert!(universe.request_execute_turn());
+        }
+        for construct in universe.constructs().values() {
+            for module in construct.modules() {
+                assert!(module.wear().is_none_or(|wear| wear.condition() == 100));
+            }
+        }
+        let base = universe.constructs().get("The_base_1").unwrap();
+        assert_eq!(100, base.production_module("PowerToOre").unwrap().stats().completed_cycles());
+        assert_eq!(0, base.production_module("PowerToOre").unwrap().stats().idle_turns_for(&IdleReason::BrokenDown));
+        assert_eq!(Some(&10), base.current_storage().get(&Product::ware("Metals")));
+    }
+}